@@ -105,5 +105,37 @@ fn bench_get(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_get);
+fn bench_build(c: &mut Criterion) {
+    let mut group = c.benchmark_group("SpanMap::build");
+
+    let spans: Vec<_> = (0..1000)
+        .map(|i| {
+            (
+                Span::new(
+                    LeftBound::Included((i * 37) % 2000),
+                    RightBound::Excluded((i * 37) % 2000 + 50),
+                ),
+                i % 10,
+            )
+        })
+        .collect();
+
+    group.bench_function("insert_1000", |b| {
+        b.iter(|| {
+            let mut map = SpanMap::<i32, i32>::new();
+            for (span, value) in spans.iter().cloned() {
+                map.insert_span(span, value);
+            }
+            black_box(map)
+        });
+    });
+
+    group.bench_function("from_spans_1000", |b| {
+        b.iter(|| black_box(SpanMap::from_spans(spans.iter().cloned())));
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_get, bench_build);
 criterion_main!(benches);
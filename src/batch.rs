@@ -0,0 +1,179 @@
+//! Batched mutation of a [`SpanMap`], merging boundaries once at the end.
+
+use std::collections::BTreeSet;
+use std::ops::RangeBounds;
+
+use crate::bounds::LeftBound;
+use crate::Span;
+use crate::SpanMap;
+
+/// A set of pending mutations on a [`SpanMap`], created by [`SpanMap::batch`].
+///
+/// Every mutation is applied to the map right away, but merging neighbouring segments with
+/// equal value sets is deferred until the batch is dropped. The result is the same as applying
+/// the mutations one by one on the map.
+#[derive(Debug)]
+pub struct Batch<'a, K, V>
+where
+    K: Clone + Ord,
+    V: Clone + Ord,
+{
+    pub(crate) map: &'a mut SpanMap<K, V>,
+
+    /// The boundaries that may have become redundant.
+    touched: BTreeSet<LeftBound<K>>,
+}
+
+impl<K, V> SpanMap<K, V>
+where
+    K: Clone + Ord,
+    V: Clone + Ord,
+{
+    /// Applies the mutations done in `f` as one batch and returns the result of `f`.
+    ///
+    /// Every [`SpanMap::insert`] or [`SpanMap::remove`] merges the segments it touched with
+    /// their neighbours. In a batch, merging is done once for all mutations, when `f` returns.
+    ///
+    /// # Examples
+    /// ```
+    /// # use span_map::SpanMap;
+    ///
+    /// let mut map = SpanMap::new();
+    /// map.batch(|b| {
+    ///     for i in 0..100 {
+    ///         b.insert(i..i + 1, "a");
+    ///     }
+    ///     b.remove(50..60, "a");
+    /// });
+    ///
+    /// let mut want = SpanMap::new();
+    /// want.insert(0..50, "a");
+    /// want.insert(60..100, "a");
+    /// assert_eq!(map, want);
+    /// ```
+    pub fn batch<T>(&mut self, f: impl FnOnce(&mut Batch<'_, K, V>) -> T) -> T {
+        let mut batch = Batch {
+            map: self,
+            touched: BTreeSet::new(),
+        };
+        f(&mut batch)
+    }
+}
+
+impl<K, V> Batch<'_, K, V>
+where
+    K: Clone + Ord,
+    V: Clone + Ord,
+{
+    /// Same as [`SpanMap::get`].
+    pub fn get(&self, key: &K) -> impl Iterator<Item = &V> {
+        self.map.get(key)
+    }
+
+    /// Same as [`SpanMap::insert`], without merging segments.
+    pub fn insert<R>(&mut self, range: R, value: V)
+    where
+        R: RangeBounds<K>,
+    {
+        self.insert_span(Span::from_range(range), value);
+    }
+
+    /// Same as [`SpanMap::remove`], without merging segments.
+    pub fn remove<R>(&mut self, range: R, value: V)
+    where
+        R: RangeBounds<K>,
+    {
+        self.remove_span(Span::from_range(range), value);
+    }
+
+    /// Same as [`SpanMap::insert_span`], without merging segments.
+    pub fn insert_span(&mut self, span: Span<K>, value: V) {
+        let touched = self.map.update_set_unmerged(span, |set| {
+            set.insert(value.clone());
+        });
+        self.touched.extend(touched);
+    }
+
+    /// Same as [`SpanMap::remove_span`], without merging segments.
+    pub fn remove_span(&mut self, span: Span<K>, value: V) {
+        let touched = self.map.update_set_unmerged(span, |set| {
+            set.remove(&value);
+        });
+        self.touched.extend(touched);
+    }
+}
+
+impl<K, V> Drop for Batch<'_, K, V>
+where
+    K: Clone + Ord,
+    V: Clone + Ord,
+{
+    fn drop(&mut self) {
+        // In ascending order, a merge only ever removes the boundary being checked, so every
+        // boundary is compared with its final left neighbour.
+        for b in std::mem::take(&mut self.touched) {
+            if self.map.m.contains_key(&b) {
+                self.map.merge_adjacent_left(b);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_same_as_one_by_one() {
+        let ops: Vec<(bool, std::ops::Range<i32>, u8)> = (0..300)
+            .map(|i| {
+                let start = (i * 7) % 40;
+                let len = (i * 13) % 9;
+                (i % 3 != 0, start..start + len, (i % 4) as u8)
+            })
+            .collect();
+
+        let mut want = SpanMap::new();
+        for (add, range, v) in ops.clone() {
+            if add {
+                want.insert(range, v);
+            } else {
+                want.remove(range, v);
+            }
+        }
+
+        let mut map = SpanMap::new();
+        map.batch(|b| {
+            for (add, range, v) in ops {
+                if add {
+                    b.insert(range, v);
+                } else {
+                    b.remove(range, v);
+                }
+            }
+        });
+
+        assert_eq!(map, want);
+    }
+
+    #[test]
+    fn test_batch_get_and_result() {
+        let mut map = SpanMap::new();
+        map.insert(0..10, "a");
+
+        let n = map.batch(|b| {
+            b.remove(0..10, "a");
+            b.insert(5..15, "b");
+            b.get(&7).count()
+        });
+        assert_eq!(n, 1);
+
+        let mut want = SpanMap::new();
+        want.insert(5..15, "b");
+        assert_eq!(map, want);
+
+        // An empty batch leaves the map unchanged
+        map.batch(|_b| {});
+        assert_eq!(map, want);
+    }
+}
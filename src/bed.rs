@@ -0,0 +1,257 @@
+//! Reading and writing [BED] interval files.
+//!
+//! A BED line is `chrom start end [name ...]`, with 0-based, half-open coordinates: the feature
+//! covers the bases `start..end`. Every chromosome is loaded into its own
+//! `SpanMap<u64, String>`, mapping the bases of every feature to its name.
+//!
+//! [BED]: https://genome.ucsc.edu/FAQ/FAQformat.html#format1
+//!
+//! # Examples
+//! ```
+//! # use span_map::bed;
+//!
+//! let input = "chr1\t100\t200\tgeneA\nchr1\t150\t250\tgeneB\nchr2\t0\t10\tgeneC\n";
+//! let maps = bed::read(input.as_bytes()).unwrap();
+//!
+//! let at: Vec<_> = maps["chr1"].get(&160).collect();
+//! assert_eq!(at, vec!["geneA", "geneB"]);
+//!
+//! let mut out = Vec::new();
+//! bed::write(&mut out, &maps).unwrap();
+//! assert_eq!(
+//!     String::from_utf8(out).unwrap(),
+//!     concat!(
+//!         "chr1\t100\t150\tgeneA\n",
+//!         "chr1\t150\t200\tgeneA\n",
+//!         "chr1\t150\t200\tgeneB\n",
+//!         "chr1\t200\t250\tgeneB\n",
+//!         "chr2\t0\t10\tgeneC\n",
+//!     )
+//! );
+//! ```
+
+use std::collections::BTreeMap;
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+
+use crate::bounds::LeftBound;
+use crate::bounds::RightBound;
+use crate::Span;
+use crate::SpanMap;
+
+/// The name given to features of a BED line without a name column.
+pub const NO_NAME: &str = ".";
+
+/// Reads a BED stream into one map per chromosome.
+///
+/// Blank lines, comments (`#`) and `track` / `browser` header lines are skipped. Columns after
+/// the name are ignored, and features without a name are named [`NO_NAME`]. Zero-length
+/// features (`start == end`) contain no base and are skipped.
+///
+/// Returns an error of kind [`io::ErrorKind::InvalidData`] for a line with fewer than three
+/// columns, a coordinate that is not a number, or `start > end`.
+pub fn read<R: BufRead>(r: R) -> io::Result<BTreeMap<String, SpanMap<u64, String>>> {
+    let mut maps: BTreeMap<String, SpanMap<u64, String>> = BTreeMap::new();
+
+    for (i, line) in r.lines().enumerate() {
+        let line = line?;
+        let line = line.trim_end();
+
+        let first = line.split_whitespace().next();
+        if line.is_empty() || line.starts_with('#') || matches!(first, Some("track" | "browser")) {
+            continue;
+        }
+
+        let invalid = |msg: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: {}", i + 1, msg),
+            )
+        };
+
+        let mut fields = line.split('\t');
+        let (Some(chrom), Some(start), Some(end)) = (fields.next(), fields.next(), fields.next())
+        else {
+            return Err(invalid(
+                "expected at least 3 tab-separated columns".to_string(),
+            ));
+        };
+        let name = fields.next().unwrap_or(NO_NAME);
+
+        let start: u64 = start
+            .parse()
+            .map_err(|e| invalid(format!("invalid start {:?}: {}", start, e)))?;
+        let end: u64 = end
+            .parse()
+            .map_err(|e| invalid(format!("invalid end {:?}: {}", end, e)))?;
+
+        if start > end {
+            return Err(invalid(format!(
+                "start {} is greater than end {}",
+                start, end
+            )));
+        }
+
+        maps.entry(chrom.to_string())
+            .or_default()
+            .insert(start..end, name.to_string());
+    }
+
+    Ok(maps)
+}
+
+/// Writes every non-empty segment of every map as BED lines, one line per value of the segment.
+///
+/// The maps are written in canonical form, see [`SpanMap::canonicalize`], so that `[1, 5]` and
+/// `[6, 8)` holding the same names are written as the single line `1..8`.
+///
+/// Returns an error of kind [`io::ErrorKind::InvalidInput`] if a segment has no end, or ends
+/// at `u64::MAX` inclusively, since it cannot be expressed as a half-open BED range.
+pub fn write<W: Write>(w: &mut W, maps: &BTreeMap<String, SpanMap<u64, String>>) -> io::Result<()> {
+    for (chrom, map) in maps {
+        let mut map = map.clone();
+        map.canonicalize();
+
+        for (span, values) in &map {
+            let (start, end) = half_open(&span).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{}: span {} can not be written as BED", chrom, span),
+                )
+            })?;
+
+            // A segment before position 0, such as `..0`, holds no base.
+            if start >= end {
+                continue;
+            }
+
+            for name in values {
+                writeln!(w, "{}\t{}\t{}\t{}", chrom, start, end, name)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Converts a span to the `start..end` BED coordinates, if it has an end.
+fn half_open(span: &Span<u64>) -> Option<(u64, u64)> {
+    let start = match span.left() {
+        LeftBound::Unbounded => 0,
+        LeftBound::Included(k) => *k,
+        LeftBound::Excluded(k) => k.checked_add(1)?,
+    };
+    let end = match span.right() {
+        RightBound::Excluded(k) => *k,
+        RightBound::Included(k) => k.checked_add(1)?,
+        RightBound::Unbounded => return None,
+    };
+
+    Some((start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read() {
+        let input = [
+            "track name=genes",
+            "browser position chr1:1-1000",
+            "# comment",
+            "",
+            "chr1\t10\t20\ta\t0\t+",
+            "chr1\t15\t30\tb",
+            "chr1\t40\t40\tinsertion",
+            "chrX\t5\t6",
+        ]
+        .join("\n");
+
+        let maps = read(input.as_bytes()).unwrap();
+        assert_eq!(maps.len(), 2);
+
+        let mut chr1 = SpanMap::new();
+        chr1.insert(10..20, "a".to_string());
+        chr1.insert(15..30, "b".to_string());
+        assert_eq!(maps["chr1"], chr1);
+
+        let mut chr_x = SpanMap::new();
+        chr_x.insert(5..6, NO_NAME.to_string());
+        assert_eq!(maps["chrX"], chr_x);
+    }
+
+    #[test]
+    fn test_read_invalid() {
+        let err = read("chr1\t10\n".as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().starts_with("line 1:"), "{}", err);
+
+        let err = read("# header\nchr1\tx\t20\n".as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(
+            err.to_string().starts_with("line 2: invalid start"),
+            "{}",
+            err
+        );
+
+        let err = read("chr1\t20\t10\n".as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_write_round_trip() {
+        let input = "chr1\t0\t5\ta\nchr1\t5\t9\ta\nchr2\t3\t4\tb\n";
+        let maps = read(input.as_bytes()).unwrap();
+
+        let mut out = Vec::new();
+        write(&mut out, &maps).unwrap();
+
+        // Adjacent features with the same name are merged
+        assert_eq!(
+            String::from_utf8(out.clone()).unwrap(),
+            "chr1\t0\t9\ta\nchr2\t3\t4\tb\n"
+        );
+        assert_eq!(read(out.as_slice()).unwrap(), maps);
+    }
+
+    #[test]
+    fn test_write_bounds() {
+        let mut map = SpanMap::new();
+        map.insert(..=3, "a".to_string());
+        map.insert_span(
+            Span::new(LeftBound::Excluded(5), RightBound::Included(7)),
+            "b".to_string(),
+        );
+
+        let mut maps = BTreeMap::new();
+        maps.insert("chr1".to_string(), map);
+
+        let mut out = Vec::new();
+        write(&mut out, &maps).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "chr1\t0\t4\ta\nchr1\t6\t8\tb\n"
+        );
+
+        // Neither `(5, 6)` nor `..0` holds a base, and `[1, 5]` and `[6, 8)` are written as one
+        let mut slivers = SpanMap::new();
+        slivers.insert(..0, "x".to_string());
+        slivers.insert(1..=5, "c".to_string());
+        slivers.insert(6..8, "c".to_string());
+        slivers.insert(..9, "d".to_string());
+        let slivers = BTreeMap::from([("chr2".to_string(), slivers)]);
+
+        let mut out = Vec::new();
+        write(&mut out, &slivers).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "chr2\t0\t1\td\nchr2\t1\t8\tc\nchr2\t1\t8\td\nchr2\t8\t9\td\n"
+        );
+
+        maps.get_mut("chr1").unwrap().insert(10.., "c".to_string());
+        let err = write(&mut Vec::new(), &maps).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
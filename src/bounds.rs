@@ -1,3 +1,36 @@
+//! The left and right bounds of a span, and the ordering between them.
+//!
+//! Unlike `std::ops::Bound`, the start and the end of a span have distinct types,
+//! [`LeftBound`] and [`RightBound`]. This gives each of them a total order that matches its
+//! position on the key axis, so `Ord::min` and `Ord::max` pick the right bound even when
+//! included and excluded bounds are mixed:
+//!
+//! ```
+//! # use span_map::bounds::{LeftBound, RightBound};
+//!
+//! // The later of two starts, e.g., for the intersection of `[5, ..` and `(5, ..`
+//! assert_eq!(
+//!     LeftBound::Included(5).max(LeftBound::Excluded(5)),
+//!     LeftBound::Excluded(5)
+//! );
+//!
+//! // The earlier of two ends, e.g., for the intersection of `.., 5]` and `.., 5)`
+//! assert_eq!(
+//!     RightBound::Included(5).min(RightBound::Excluded(5)),
+//!     RightBound::Excluded(5)
+//! );
+//! ```
+//!
+//! A left bound can also be compared with a right bound: `left <= right` holds if and only if
+//! the span `(left, right)` contains at least one key.
+//!
+//! ```
+//! # use span_map::bounds::{LeftBound, RightBound};
+//!
+//! assert!(LeftBound::Included(5) <= RightBound::Included(5));
+//! assert!(LeftBound::Included(5) > RightBound::Excluded(5));
+//! ```
+
 mod left;
 mod right;
 
@@ -2,10 +2,21 @@ use std::cmp::Ordering;
 use std::collections::Bound;
 use std::fmt;
 
+use crate::bounds::RightBound;
+use crate::Step;
+
+/// The start of a span.
+///
+/// Left bounds are ordered by where they start on the key axis: `Unbounded` is the smallest, and
+/// for the same key `Included` comes before `Excluded`.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LeftBound<T> {
+    /// The span starts at negative infinity.
     Unbounded,
+    /// The span starts at, and includes, the key.
     Included(T),
+    /// The span starts right after the key.
     Excluded(T),
 }
 
@@ -22,6 +33,109 @@ where
     }
 }
 
+impl<T> LeftBound<T> {
+    /// Returns the key of this bound, or `None` if it is unbounded.
+    pub fn key(&self) -> Option<&T> {
+        match self {
+            LeftBound::Unbounded => None,
+            LeftBound::Included(t) | LeftBound::Excluded(t) => Some(t),
+        }
+    }
+
+    /// Converts the key of this bound with `f`, keeping the kind of the bound.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> LeftBound<U> {
+        match self {
+            LeftBound::Unbounded => LeftBound::Unbounded,
+            LeftBound::Included(t) => LeftBound::Included(f(t)),
+            LeftBound::Excluded(t) => LeftBound::Excluded(f(t)),
+        }
+    }
+
+    /// Returns `true` if `key` is on the inner side of this bound, i.e., a span starting with
+    /// this bound can contain `key`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use span_map::bounds::LeftBound;
+    ///
+    /// assert!(LeftBound::Included(5).admits(&5));
+    /// assert!(!LeftBound::Excluded(5).admits(&5));
+    /// assert!(LeftBound::Excluded(5).admits(&6));
+    /// assert!(LeftBound::Unbounded.admits(&i32::MIN));
+    /// ```
+    pub fn admits(&self, key: &T) -> bool
+    where
+        T: Ord,
+    {
+        match self {
+            LeftBound::Unbounded => true,
+            LeftBound::Included(t) => t <= key,
+            LeftBound::Excluded(t) => t < key,
+        }
+    }
+
+    /// Converts this left bound into a complementary right bound that would create
+    /// adjacent non-overlapping ranges.
+    ///
+    /// This is the counterpart of [`RightBound::adjacent_left`]. For example, if we have two
+    /// ranges:
+    /// - Range1: (..., LeftBound::Included(5).adjacent_right())
+    /// - Range2: (LeftBound::Included(5), ...)
+    ///
+    /// # Examples
+    /// ```
+    /// # use span_map::bounds::{LeftBound, RightBound};
+    ///
+    /// let l1 = LeftBound::Included(5);
+    /// assert_eq!(l1.adjacent_right(), Some(RightBound::Excluded(5)));
+    ///
+    /// let l2 = LeftBound::Excluded(5);
+    /// assert_eq!(l2.adjacent_right(), Some(RightBound::Included(5)));
+    ///
+    /// let l3 = LeftBound::<i32>::Unbounded;
+    /// assert_eq!(l3.adjacent_right(), None);
+    /// ```
+    pub fn adjacent_right(&self) -> Option<RightBound<T>>
+    where
+        T: Clone,
+    {
+        match self {
+            LeftBound::Unbounded => None,
+            LeftBound::Included(t) => Some(RightBound::Excluded(t.clone())),
+            LeftBound::Excluded(t) => Some(RightBound::Included(t.clone())),
+        }
+    }
+
+    /// Converts this left bound into the equivalent included bound, for discrete keys.
+    ///
+    /// An excluded bound becomes an included bound on the successor of its key. The bound is
+    /// returned unchanged if it is unbounded, already included, or if its key has no successor.
+    ///
+    /// # Examples
+    /// ```
+    /// # use span_map::bounds::LeftBound;
+    ///
+    /// assert_eq!(LeftBound::Excluded(5).canonical(), LeftBound::Included(6));
+    /// assert_eq!(LeftBound::Included(5).canonical(), LeftBound::Included(5));
+    /// assert_eq!(
+    ///     LeftBound::Excluded(u8::MAX).canonical(),
+    ///     LeftBound::Excluded(u8::MAX)
+    /// );
+    /// ```
+    pub fn canonical(self) -> Self
+    where
+        T: Step,
+    {
+        match self {
+            LeftBound::Excluded(t) => match t.successor() {
+                Some(s) => LeftBound::Included(s),
+                None => LeftBound::Excluded(t),
+            },
+            _ => self,
+        }
+    }
+}
+
 impl<T> From<Bound<T>> for LeftBound<T> {
     fn from(bound: Bound<T>) -> Self {
         match bound {
@@ -205,6 +319,100 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_key_and_map() {
+        assert_eq!(LeftBound::<i32>::Unbounded.key(), None);
+        assert_eq!(LeftBound::Included(5).key(), Some(&5));
+        assert_eq!(LeftBound::Excluded(5).key(), Some(&5));
+
+        assert_eq!(
+            LeftBound::<i32>::Unbounded.map(|k| k + 1),
+            LeftBound::Unbounded
+        );
+        assert_eq!(
+            LeftBound::Included(5).map(|k| k + 1),
+            LeftBound::Included(6)
+        );
+        assert_eq!(
+            LeftBound::Excluded(5).map(|k| k.to_string()),
+            LeftBound::Excluded("5".to_string())
+        );
+    }
+
+    #[test]
+    fn test_admits() {
+        assert!(LeftBound::Unbounded.admits(&i32::MIN));
+
+        assert!(!LeftBound::Included(5).admits(&4));
+        assert!(LeftBound::Included(5).admits(&5));
+        assert!(LeftBound::Included(5).admits(&6));
+
+        assert!(!LeftBound::Excluded(5).admits(&4));
+        assert!(!LeftBound::Excluded(5).admits(&5));
+        assert!(LeftBound::Excluded(5).admits(&6));
+    }
+
+    #[test]
+    fn test_min_max() {
+        use std::cmp::max;
+        use std::cmp::min;
+
+        assert_eq!(
+            max(LeftBound::Included(5), LeftBound::Excluded(5)),
+            LeftBound::Excluded(5)
+        );
+        assert_eq!(
+            min(LeftBound::Included(5), LeftBound::Excluded(5)),
+            LeftBound::Included(5)
+        );
+        assert_eq!(
+            min(LeftBound::Unbounded, LeftBound::Excluded(5)),
+            LeftBound::Unbounded
+        );
+        assert_eq!(
+            max(LeftBound::Excluded(4), LeftBound::Included(5)),
+            LeftBound::Included(5)
+        );
+    }
+
+    #[test]
+    fn test_adjacent_right() {
+        assert_eq!(LeftBound::<usize>::Unbounded.adjacent_right(), None);
+
+        assert_eq!(
+            LeftBound::Included(5).adjacent_right(),
+            Some(RightBound::Excluded(5))
+        );
+
+        assert_eq!(
+            LeftBound::Excluded(5).adjacent_right(),
+            Some(RightBound::Included(5))
+        );
+
+        // Round trip
+        for l in [LeftBound::Included(5), LeftBound::Excluded(5)] {
+            assert_eq!(l.adjacent_right().unwrap().adjacent_left(), Some(l));
+        }
+    }
+
+    #[test]
+    fn test_left_bound_canonical() {
+        assert_eq!(
+            LeftBound::<i32>::Unbounded.canonical(),
+            LeftBound::Unbounded
+        );
+        assert_eq!(LeftBound::Included(5).canonical(), LeftBound::Included(5));
+        assert_eq!(LeftBound::Excluded(5).canonical(), LeftBound::Included(6));
+        assert_eq!(
+            LeftBound::Excluded(i32::MAX).canonical(),
+            LeftBound::Excluded(i32::MAX)
+        );
+        assert_eq!(
+            LeftBound::Excluded('a').canonical(),
+            LeftBound::Included('b')
+        );
+    }
+
     #[test]
     fn test_left_bound_ord() {
         // Test Unbounded comparisons
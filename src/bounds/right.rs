@@ -3,11 +3,20 @@ use std::collections::Bound;
 use std::fmt;
 
 use crate::bounds::LeftBound;
+use crate::Step;
 
+/// The end of a span.
+///
+/// Right bounds are ordered by where they end on the key axis: `Unbounded` is the greatest, and
+/// for the same key `Excluded` comes before `Included`.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RightBound<T> {
+    /// The span ends right before the key.
     Excluded(T),
+    /// The span ends at, and includes, the key.
     Included(T),
+    /// The span ends at positive infinity.
     Unbounded,
 }
 
@@ -25,6 +34,46 @@ where
 }
 
 impl<T> RightBound<T> {
+    /// Returns the key of this bound, or `None` if it is unbounded.
+    pub fn key(&self) -> Option<&T> {
+        match self {
+            RightBound::Unbounded => None,
+            RightBound::Included(t) | RightBound::Excluded(t) => Some(t),
+        }
+    }
+
+    /// Converts the key of this bound with `f`, keeping the kind of the bound.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> RightBound<U> {
+        match self {
+            RightBound::Excluded(t) => RightBound::Excluded(f(t)),
+            RightBound::Included(t) => RightBound::Included(f(t)),
+            RightBound::Unbounded => RightBound::Unbounded,
+        }
+    }
+
+    /// Returns `true` if `key` is on the inner side of this bound, i.e., a span ending with
+    /// this bound can contain `key`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use span_map::bounds::RightBound;
+    ///
+    /// assert!(RightBound::Included(5).admits(&5));
+    /// assert!(!RightBound::Excluded(5).admits(&5));
+    /// assert!(RightBound::Excluded(5).admits(&4));
+    /// assert!(RightBound::Unbounded.admits(&i32::MAX));
+    /// ```
+    pub fn admits(&self, key: &T) -> bool
+    where
+        T: Ord,
+    {
+        match self {
+            RightBound::Excluded(t) => key < t,
+            RightBound::Included(t) => key <= t,
+            RightBound::Unbounded => true,
+        }
+    }
+
     /// Converts this right bound into a complementary left bound that would create
     /// adjacent non-overlapping ranges.
     ///
@@ -45,6 +94,18 @@ impl<T> RightBound<T> {
     /// let r3 = RightBound::<i32>::Unbounded;
     /// assert_eq!(r3.adjacent_left(), None);
     /// ```
+    ///
+    /// For discrete keys, the result can be converted to inclusive form with
+    /// [`LeftBound::canonical`]:
+    /// ```
+    /// # use span_map::bounds::{LeftBound, RightBound};
+    ///
+    /// let r1 = RightBound::Included(5);
+    /// assert_eq!(
+    ///     r1.adjacent_left().map(LeftBound::canonical),
+    ///     Some(LeftBound::Included(6))
+    /// );
+    /// ```
     pub fn adjacent_left(&self) -> Option<LeftBound<T>>
     where
         T: Clone,
@@ -55,6 +116,35 @@ impl<T> RightBound<T> {
             RightBound::Excluded(t) => Some(LeftBound::Included(t.clone())),
         }
     }
+
+    /// Converts this right bound into the equivalent included bound, for discrete keys.
+    ///
+    /// An excluded bound becomes an included bound on the predecessor of its key. The bound is
+    /// returned unchanged if it is unbounded, already included, or if its key has no predecessor.
+    ///
+    /// # Examples
+    /// ```
+    /// # use span_map::bounds::RightBound;
+    ///
+    /// assert_eq!(RightBound::Excluded(5).canonical(), RightBound::Included(4));
+    /// assert_eq!(RightBound::Included(5).canonical(), RightBound::Included(5));
+    /// assert_eq!(
+    ///     RightBound::Excluded(0u8).canonical(),
+    ///     RightBound::Excluded(0)
+    /// );
+    /// ```
+    pub fn canonical(self) -> Self
+    where
+        T: Step,
+    {
+        match self {
+            RightBound::Excluded(t) => match t.predecessor() {
+                Some(p) => RightBound::Included(p),
+                None => RightBound::Excluded(t),
+            },
+            _ => self,
+        }
+    }
 }
 
 impl<T> PartialOrd for RightBound<T>
@@ -356,6 +446,80 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_key_and_map() {
+        assert_eq!(RightBound::<i32>::Unbounded.key(), None);
+        assert_eq!(RightBound::Included(5).key(), Some(&5));
+        assert_eq!(RightBound::Excluded(5).key(), Some(&5));
+
+        assert_eq!(
+            RightBound::<i32>::Unbounded.map(|k| k + 1),
+            RightBound::Unbounded
+        );
+        assert_eq!(
+            RightBound::Included(5).map(|k| k + 1),
+            RightBound::Included(6)
+        );
+        assert_eq!(
+            RightBound::Excluded(5).map(|k| k.to_string()),
+            RightBound::Excluded("5".to_string())
+        );
+    }
+
+    #[test]
+    fn test_admits() {
+        assert!(RightBound::Unbounded.admits(&i32::MAX));
+
+        assert!(RightBound::Included(5).admits(&4));
+        assert!(RightBound::Included(5).admits(&5));
+        assert!(!RightBound::Included(5).admits(&6));
+
+        assert!(RightBound::Excluded(5).admits(&4));
+        assert!(!RightBound::Excluded(5).admits(&5));
+        assert!(!RightBound::Excluded(5).admits(&6));
+    }
+
+    #[test]
+    fn test_min_max() {
+        use std::cmp::max;
+        use std::cmp::min;
+
+        assert_eq!(
+            max(RightBound::Included(5), RightBound::Excluded(5)),
+            RightBound::Included(5)
+        );
+        assert_eq!(
+            min(RightBound::Included(5), RightBound::Excluded(5)),
+            RightBound::Excluded(5)
+        );
+        assert_eq!(
+            max(RightBound::Unbounded, RightBound::Excluded(5)),
+            RightBound::Unbounded
+        );
+        assert_eq!(
+            min(RightBound::Included(4), RightBound::Excluded(5)),
+            RightBound::Included(4)
+        );
+    }
+
+    #[test]
+    fn test_right_bound_canonical() {
+        assert_eq!(
+            RightBound::<i32>::Unbounded.canonical(),
+            RightBound::Unbounded
+        );
+        assert_eq!(RightBound::Included(5).canonical(), RightBound::Included(5));
+        assert_eq!(RightBound::Excluded(5).canonical(), RightBound::Included(4));
+        assert_eq!(
+            RightBound::Excluded(i32::MIN).canonical(),
+            RightBound::Excluded(i32::MIN)
+        );
+        assert_eq!(
+            RightBound::Excluded('b').canonical(),
+            RightBound::Included('a')
+        );
+    }
+
     #[test]
     fn test_next_left() {
         // Test Unbounded case
@@ -0,0 +1,361 @@
+//! Reporting the effective changes of mutations, for callers that have to react to them.
+
+use std::collections::BTreeSet;
+use std::ops::RangeBounds;
+
+use crate::bounds::RightBound;
+use crate::Batch;
+use crate::PatchOp;
+use crate::Span;
+use crate::SpanError;
+use crate::SpanMap;
+use crate::SpanPatch;
+
+/// Whether a value was added to or removed from a span.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// The value was absent from the span before, and is present now.
+    Added,
+    /// The value was present in the span before, and is absent now.
+    Removed,
+}
+
+/// A change in the membership of a value over a span, as returned by
+/// [`SpanMap::insert_tracked`], [`SpanMap::remove_tracked`] and [`SpanMap::apply_tracked`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change<K, V>
+where
+    K: Ord,
+{
+    /// The keys whose value set changed.
+    pub span: Span<K>,
+    /// The value added or removed.
+    pub value: V,
+    /// Whether `value` was added or removed.
+    pub kind: ChangeKind,
+}
+
+impl<K, V> From<Change<K, V>> for PatchOp<K, V>
+where
+    K: Ord,
+{
+    /// Returns the operation that makes the change.
+    fn from(change: Change<K, V>) -> Self {
+        match change.kind {
+            ChangeKind::Added => PatchOp::Insert(change.span, change.value),
+            ChangeKind::Removed => PatchOp::Remove(change.span, change.value),
+        }
+    }
+}
+
+impl<K, V> SpanMap<K, V>
+where
+    K: Clone + Ord,
+    V: Clone + Ord,
+{
+    /// Same as [`SpanMap::insert`], but returns the spans where `value` was actually added, in
+    /// key order.
+    ///
+    /// Parts of `range` where `value` was already present are not reported.
+    ///
+    /// # Examples
+    /// ```
+    /// # use span_map::Change;
+    /// # use span_map::ChangeKind;
+    /// # use span_map::Span;
+    /// # use span_map::SpanMap;
+    ///
+    /// let mut map = SpanMap::new();
+    /// map.insert(3..5, "a");
+    ///
+    /// let changes = map.insert_tracked(0..10, "a");
+    /// assert_eq!(
+    ///     changes,
+    ///     vec![
+    ///         Change {
+    ///             span: Span::from_range(0..3),
+    ///             value: "a",
+    ///             kind: ChangeKind::Added
+    ///         },
+    ///         Change {
+    ///             span: Span::from_range(5..10),
+    ///             value: "a",
+    ///             kind: ChangeKind::Added
+    ///         },
+    ///     ]
+    /// );
+    /// ```
+    pub fn insert_tracked<R>(&mut self, range: R, value: V) -> Vec<Change<K, V>>
+    where
+        R: RangeBounds<K>,
+    {
+        let span = Span::from_range(range);
+        let changes = self.changes_in(&span, &value, ChangeKind::Added);
+        self.insert_span(span, value);
+        changes
+    }
+
+    /// Same as [`SpanMap::remove`], but returns the spans where `value` was actually removed,
+    /// in key order.
+    ///
+    /// Parts of `range` where `value` was already absent are not reported.
+    pub fn remove_tracked<R>(&mut self, range: R, value: V) -> Vec<Change<K, V>>
+    where
+        R: RangeBounds<K>,
+    {
+        let span = Span::from_range(range);
+        let changes = self.changes_in(&span, &value, ChangeKind::Removed);
+        self.remove_span(span, value);
+        changes
+    }
+
+    /// Same as [`SpanMap::apply`], but returns the changes actually made by every operation of
+    /// `patch`, in order.
+    ///
+    /// A later operation may undo the changes of an earlier one; both are reported. Other bulk
+    /// operations, such as [`SpanMap::append`] or [`SpanMap::splice`], do not report changes:
+    /// compare the map before and after with [`SpanMap::diff`] instead.
+    ///
+    /// # Examples
+    /// ```
+    /// # use span_map::ChangeKind;
+    /// # use span_map::Span;
+    /// # use span_map::SpanMap;
+    /// # use span_map::SpanPatch;
+    ///
+    /// let mut map = SpanMap::new();
+    /// map.insert(0..10, "a");
+    ///
+    /// let mut patch = SpanPatch::new();
+    /// patch.insert(5..15, "a");
+    ///
+    /// let changes = map.apply_tracked(&patch).unwrap();
+    /// assert_eq!(changes.len(), 1);
+    /// assert_eq!(changes[0].span, Span::from_range(10..15));
+    /// assert_eq!(changes[0].kind, ChangeKind::Added);
+    /// ```
+    pub fn apply_tracked(
+        &mut self,
+        patch: &SpanPatch<K, V>,
+    ) -> Result<Vec<Change<K, V>>, SpanError> {
+        patch.validate()?;
+
+        let changes = self.batch(|b| {
+            let mut changes = Vec::new();
+            for op in patch.ops().iter().cloned() {
+                match op {
+                    PatchOp::Insert(span, value) => changes.extend(b.insert_tracked(span, value)),
+                    PatchOp::Remove(span, value) => changes.extend(b.remove_tracked(span, value)),
+                }
+            }
+            changes
+        });
+
+        Ok(changes)
+    }
+
+    /// Returns the changes that adding or removing `value` over `span` would make.
+    ///
+    /// Only the segments overlapping `span` are visited. Segments do not have to be merged, so
+    /// that this can be used inside a [`Batch`].
+    fn changes_in(&self, span: &Span<K>, value: &V, kind: ChangeKind) -> Vec<Change<K, V>> {
+        let mut changes: Vec<Change<K, V>> = Vec::new();
+        if span.is_empty() {
+            return changes;
+        }
+
+        let changes_set = |set: &BTreeSet<V>| match kind {
+            ChangeKind::Added => !set.contains(value),
+            ChangeKind::Removed => set.contains(value),
+        };
+
+        // Safe unwrap(): Unbounded is always present
+        let (first, _set) = self.m.range(..=span.left.clone()).next_back().unwrap();
+
+        let mut it = self.m.range(first.clone()..).peekable();
+        let mut extends_last = false;
+
+        while let Some((b, set)) = it.next() {
+            if span.right < *b {
+                break;
+            }
+
+            let right = match it.peek() {
+                // Safe unwrap(): only the first boundary is Unbounded
+                Some((next, _set)) => next.adjacent_right().unwrap(),
+                None => RightBound::Unbounded,
+            };
+
+            let piece = Span::new(b.clone(), right).intersection(span);
+
+            match piece {
+                Some(piece) if changes_set(set) => {
+                    if extends_last {
+                        // Segments are contiguous, the previous change extends up to here.
+                        changes.last_mut().unwrap().span.right = piece.right;
+                    } else {
+                        changes.push(Change {
+                            span: piece,
+                            value: value.clone(),
+                            kind,
+                        });
+                    }
+                    extends_last = true;
+                }
+                _ => extends_last = false,
+            }
+        }
+
+        changes
+    }
+}
+
+impl<K, V> Batch<'_, K, V>
+where
+    K: Clone + Ord,
+    V: Clone + Ord,
+{
+    /// Same as [`SpanMap::insert_tracked`], without merging segments.
+    pub fn insert_tracked<R>(&mut self, range: R, value: V) -> Vec<Change<K, V>>
+    where
+        R: RangeBounds<K>,
+    {
+        let span = Span::from_range(range);
+        let changes = self.map.changes_in(&span, &value, ChangeKind::Added);
+        self.insert_span(span, value);
+        changes
+    }
+
+    /// Same as [`SpanMap::remove_tracked`], without merging segments.
+    pub fn remove_tracked<R>(&mut self, range: R, value: V) -> Vec<Change<K, V>>
+    where
+        R: RangeBounds<K>,
+    {
+        let span = Span::from_range(range);
+        let changes = self.map.changes_in(&span, &value, ChangeKind::Removed);
+        self.remove_span(span, value);
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounds::LeftBound;
+
+    fn added(range: impl RangeBounds<i32>, value: u8) -> Change<i32, u8> {
+        Change {
+            span: Span::from_range(range),
+            value,
+            kind: ChangeKind::Added,
+        }
+    }
+
+    fn removed(range: impl RangeBounds<i32>, value: u8) -> Change<i32, u8> {
+        Change {
+            span: Span::from_range(range),
+            value,
+            kind: ChangeKind::Removed,
+        }
+    }
+
+    #[test]
+    fn test_insert_tracked() {
+        let mut map = SpanMap::new();
+        assert_eq!(map.insert_tracked(0..10, 1), vec![added(0..10, 1)]);
+        assert_eq!(map.insert_tracked(2..5, 1), vec![]);
+
+        // Other values split segments, but do not split the change
+        map.insert(3..4, 2);
+        assert_eq!(
+            map.insert_tracked(..20, 1),
+            vec![added(..0, 1), added(10..20, 1)]
+        );
+
+        #[allow(clippy::reversed_empty_ranges)]
+        let empty = map.insert_tracked(5..1, 3);
+        assert_eq!(empty, vec![]);
+    }
+
+    #[test]
+    fn test_remove_tracked() {
+        let mut map = SpanMap::new();
+        map.insert(0..5, 1);
+        map.insert(8..=10, 1);
+        map.insert(2..9, 2);
+
+        assert_eq!(
+            map.remove_tracked(3.., 1),
+            vec![
+                removed(3..5, 1),
+                Change {
+                    span: Span::new(LeftBound::Included(8), RightBound::Included(10)),
+                    value: 1,
+                    kind: ChangeKind::Removed,
+                },
+            ]
+        );
+        assert_eq!(map.remove_tracked(.., 3), vec![]);
+
+        let mut want = SpanMap::new();
+        want.insert(0..3, 1);
+        want.insert(2..9, 2);
+        assert_eq!(map, want);
+    }
+
+    #[test]
+    fn test_apply_tracked() {
+        let mut map = SpanMap::new();
+        map.insert(0..10, 1);
+
+        let mut patch = SpanPatch::new();
+        patch.insert(5..15, 1);
+        patch.remove(..3, 1);
+        patch.remove(20..30, 2);
+        patch.insert(12..20, 2);
+
+        let mut want = map.clone();
+        want.apply(&patch).unwrap();
+
+        let changes = map.apply_tracked(&patch).unwrap();
+        assert_eq!(
+            changes,
+            vec![added(10..15, 1), removed(0..3, 1), added(12..20, 2)]
+        );
+        assert_eq!(map, want);
+
+        // An invalid patch reports nothing and leaves the map unchanged
+        #[allow(clippy::reversed_empty_ranges)]
+        patch.insert(5..1, 3);
+        assert_eq!(map.apply_tracked(&patch), Err(SpanError::Inverted));
+        assert_eq!(map, want);
+    }
+
+    #[test]
+    fn test_tracked_in_batch() {
+        let mut map = SpanMap::new();
+        let changes = map.batch(|b| {
+            let mut changes = Vec::new();
+            for i in 0..10 {
+                changes.extend(b.insert_tracked(i..i + 1, 1));
+            }
+            changes.extend(b.insert_tracked(0..12, 1));
+            changes.extend(b.remove_tracked(4..6, 1));
+            changes
+        });
+
+        let mut want = (0..10).map(|i| added(i..i + 1, 1)).collect::<Vec<_>>();
+        want.push(added(10..12, 1));
+        want.push(removed(4..6, 1));
+        assert_eq!(changes, want);
+
+        // The changes, applied as operations, rebuild the map
+        let mut rebuilt = SpanMap::new();
+        let mut patch = SpanPatch::new();
+        for change in changes {
+            patch.push(change.into());
+        }
+        rebuilt.apply(&patch).unwrap();
+        assert_eq!(rebuilt, map);
+    }
+}
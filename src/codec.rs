@@ -0,0 +1,501 @@
+//! A compact, versioned binary encoding of [`SpanMap`].
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+
+use crate::bounds::LeftBound;
+use crate::SpanMap;
+
+const MAGIC: &[u8; 4] = b"SPMP";
+const VERSION: u8 = 1;
+
+const TAG_UNBOUNDED: u8 = 0;
+const TAG_INCLUDED: u8 = 1;
+const TAG_EXCLUDED: u8 = 2;
+
+/// Encodes the keys of a [`SpanMap`] for [`SpanMap::encode`].
+///
+/// Keys are written in increasing order, and every key but the first is given the key written
+/// before it, so that an implementation can store the difference instead of the key itself.
+/// The integer implementations write the first key as a varint, and every following key as the
+/// varint distance to the previous one.
+pub trait KeyCodec: Sized {
+    /// Writes `self`, given the previous key written, if any.
+    fn encode_key<W: Write>(&self, prev: Option<&Self>, w: &mut W) -> io::Result<()>;
+
+    /// Reads a key written by [`KeyCodec::encode_key`] with the same `prev`.
+    fn decode_key<R: Read>(prev: Option<&Self>, r: &mut R) -> io::Result<Self>;
+}
+
+/// Encodes the values of a [`SpanMap`] for [`SpanMap::encode`].
+pub trait ValueCodec: Sized {
+    /// Writes `self`.
+    fn encode_value<W: Write>(&self, w: &mut W) -> io::Result<()>;
+
+    /// Reads a value written by [`ValueCodec::encode_value`].
+    fn decode_value<R: Read>(r: &mut R) -> io::Result<Self>;
+}
+
+impl<K, V> SpanMap<K, V>
+where
+    K: Clone + Ord,
+    V: Clone + Ord,
+{
+    /// Writes the map in a compact, versioned binary format.
+    ///
+    /// The format is:
+    ///
+    /// ```text
+    /// magic      b"SPMP"
+    /// version    u8, currently 1
+    /// sets       varint count, then for every distinct value set:
+    ///                varint length, then every value with ValueCodec
+    /// boundaries varint count, then for every boundary in key order:
+    ///                u8 tag: 0 = Unbounded, 1 = Included, 2 = Excluded
+    ///                the key with KeyCodec, unless Unbounded
+    ///                varint index of the value set starting at this boundary
+    /// ```
+    ///
+    /// Varints are unsigned LEB128. Every distinct value set is written once, and boundaries
+    /// refer to it by index, so a set repeated over many segments costs a single varint per
+    /// segment.
+    ///
+    /// # Examples
+    /// ```
+    /// # use span_map::SpanMap;
+    ///
+    /// let mut map = SpanMap::new();
+    /// map.insert(1_000_000..1_000_005, "a".to_string());
+    /// map.insert(1_000_003..1_000_008, "b".to_string());
+    ///
+    /// let mut buf = Vec::new();
+    /// map.encode(&mut buf).unwrap();
+    ///
+    /// let decoded = SpanMap::<u64, String>::decode(&mut buf.as_slice()).unwrap();
+    /// assert_eq!(decoded, map);
+    /// ```
+    pub fn encode<W: Write>(&self, w: &mut W) -> io::Result<()>
+    where
+        K: KeyCodec,
+        V: ValueCodec,
+    {
+        let mut dict: BTreeMap<&BTreeSet<V>, usize> = BTreeMap::new();
+        let mut sets = Vec::new();
+        for set in self.m.values() {
+            dict.entry(set).or_insert_with(|| {
+                sets.push(set);
+                sets.len() - 1
+            });
+        }
+
+        w.write_all(MAGIC)?;
+        w.write_all(&[VERSION])?;
+
+        write_varint(w, sets.len() as u128)?;
+        for set in sets {
+            write_varint(w, set.len() as u128)?;
+            for v in set {
+                v.encode_value(w)?;
+            }
+        }
+
+        write_varint(w, self.m.len() as u128)?;
+        let mut prev: Option<&K> = None;
+        for (b, set) in &self.m {
+            match b {
+                LeftBound::Unbounded => w.write_all(&[TAG_UNBOUNDED])?,
+                LeftBound::Included(k) | LeftBound::Excluded(k) => {
+                    let tag = if matches!(b, LeftBound::Included(_)) {
+                        TAG_INCLUDED
+                    } else {
+                        TAG_EXCLUDED
+                    };
+                    w.write_all(&[tag])?;
+                    k.encode_key(prev, w)?;
+                    prev = Some(k);
+                }
+            }
+            write_varint(w, dict[set] as u128)?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads a map written by [`SpanMap::encode`].
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidData`] if the input is not a valid
+    /// encoding, e.g., it has an unknown version, or its boundaries are not in increasing order.
+    pub fn decode<R: Read>(r: &mut R) -> io::Result<Self>
+    where
+        K: KeyCodec,
+        V: ValueCodec,
+    {
+        let mut magic = [0u8; 4];
+        r.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a SpanMap encoding: bad magic"));
+        }
+
+        let version = read_u8(r)?;
+        if version != VERSION {
+            return Err(invalid_data(format!(
+                "unsupported SpanMap encoding version: {}",
+                version
+            )));
+        }
+
+        let mut sets = Vec::new();
+        for _ in 0..read_varint(r)? {
+            let mut set = BTreeSet::new();
+            for _ in 0..read_varint(r)? {
+                set.insert(V::decode_value(r)?);
+            }
+            sets.push(set);
+        }
+
+        let mut m: BTreeMap<LeftBound<K>, BTreeSet<V>> = BTreeMap::new();
+        let mut prev: Option<K> = None;
+
+        for i in 0..read_varint(r)? {
+            let b = match read_u8(r)? {
+                TAG_UNBOUNDED if i == 0 => LeftBound::Unbounded,
+                tag @ (TAG_INCLUDED | TAG_EXCLUDED) if i > 0 => {
+                    let k = K::decode_key(prev.as_ref(), r)?;
+                    prev = Some(k.clone());
+                    if tag == TAG_INCLUDED {
+                        LeftBound::Included(k)
+                    } else {
+                        LeftBound::Excluded(k)
+                    }
+                }
+                tag => {
+                    return Err(invalid_data(format!(
+                        "invalid boundary tag {} at boundary {}",
+                        tag, i
+                    )))
+                }
+            };
+
+            if let Some((last, _set)) = m.last_key_value() {
+                if last >= &b {
+                    return Err(invalid_data("boundaries are not in increasing order"));
+                }
+            }
+
+            let index = read_varint(r)?;
+            let set = usize::try_from(index)
+                .ok()
+                .and_then(|i| sets.get(i))
+                .ok_or_else(|| invalid_data(format!("value set index out of range: {}", index)))?;
+
+            m.insert(b, set.clone());
+        }
+
+        if m.is_empty() {
+            return Err(invalid_data("missing the unbounded boundary"));
+        }
+
+        let mut map = SpanMap { m };
+        map.merge_all_adjacent();
+        Ok(map)
+    }
+}
+
+fn invalid_data(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+fn read_u8<R: Read>(r: &mut R) -> io::Result<u8> {
+    let mut buf = [0u8; 1];
+    r.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn write_varint<W: Write>(w: &mut W, mut n: u128) -> io::Result<()> {
+    loop {
+        let byte = (n & 0x7f) as u8;
+        n >>= 7;
+        if n == 0 {
+            return w.write_all(&[byte]);
+        }
+        w.write_all(&[byte | 0x80])?;
+    }
+}
+
+fn read_varint<R: Read>(r: &mut R) -> io::Result<u128> {
+    let mut n: u128 = 0;
+    let mut shift = 0;
+    loop {
+        let byte = read_u8(r)?;
+        let bits = (byte & 0x7f) as u128;
+        if shift >= 128 || (shift > 0 && bits >> (128 - shift) != 0) {
+            return Err(invalid_data("varint overflows 128 bits"));
+        }
+        n |= bits << shift;
+        if byte & 0x80 == 0 {
+            return Ok(n);
+        }
+        shift += 7;
+    }
+}
+
+macro_rules! impl_unsigned_codec {
+    ($($t:ty),*) => {
+        $(
+            impl ValueCodec for $t {
+                fn encode_value<W: Write>(&self, w: &mut W) -> io::Result<()> {
+                    write_varint(w, *self as u128)
+                }
+
+                fn decode_value<R: Read>(r: &mut R) -> io::Result<Self> {
+                    let n = read_varint(r)?;
+                    <$t>::try_from(n).map_err(|_| invalid_data("integer out of range"))
+                }
+            }
+
+            impl_key_codec!($t, $t);
+        )*
+    };
+}
+
+macro_rules! impl_signed_codec {
+    ($($t:ty => $u:ty),*) => {
+        $(
+            // Zigzag encoding, so that small negative values get short varints.
+            impl ValueCodec for $t {
+                fn encode_value<W: Write>(&self, w: &mut W) -> io::Result<()> {
+                    let v = *self as i128;
+                    write_varint(w, ((v << 1) ^ (v >> 127)) as u128)
+                }
+
+                fn decode_value<R: Read>(r: &mut R) -> io::Result<Self> {
+                    let n = read_varint(r)?;
+                    let v = ((n >> 1) as i128) ^ -((n & 1) as i128);
+                    <$t>::try_from(v).map_err(|_| invalid_data("integer out of range"))
+                }
+            }
+
+            impl_key_codec!($t, $u);
+        )*
+    };
+}
+
+/// Keys after the first are written as the distance to the previous key, computed in the
+/// unsigned type of the same width so that it never overflows.
+macro_rules! impl_key_codec {
+    ($t:ty, $u:ty) => {
+        impl KeyCodec for $t {
+            fn encode_key<W: Write>(&self, prev: Option<&Self>, w: &mut W) -> io::Result<()> {
+                match prev {
+                    None => self.encode_value(w),
+                    Some(p) => write_varint(w, (*self as $u).wrapping_sub(*p as $u) as u128),
+                }
+            }
+
+            fn decode_key<R: Read>(prev: Option<&Self>, r: &mut R) -> io::Result<Self> {
+                match prev {
+                    None => Self::decode_value(r),
+                    Some(p) => {
+                        let delta = <$u>::try_from(read_varint(r)?)
+                            .map_err(|_| invalid_data("key delta out of range"))?;
+                        Ok((*p as $u).wrapping_add(delta) as $t)
+                    }
+                }
+            }
+        }
+    };
+}
+
+impl_unsigned_codec!(u8, u16, u32, u64, u128, usize);
+impl_signed_codec!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize);
+
+impl ValueCodec for String {
+    fn encode_value<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_varint(w, self.len() as u128)?;
+        w.write_all(self.as_bytes())
+    }
+
+    fn decode_value<R: Read>(r: &mut R) -> io::Result<Self> {
+        let len = read_varint(r)?;
+        let len = u64::try_from(len).map_err(|_| invalid_data("string length out of range"))?;
+
+        let mut buf = Vec::new();
+        r.take(len).read_to_end(&mut buf)?;
+        if buf.len() as u64 != len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        String::from_utf8(buf).map_err(|e| invalid_data(e.to_string()))
+    }
+}
+
+impl KeyCodec for String {
+    fn encode_key<W: Write>(&self, _prev: Option<&Self>, w: &mut W) -> io::Result<()> {
+        self.encode_value(w)
+    }
+
+    fn decode_key<R: Read>(_prev: Option<&Self>, r: &mut R) -> io::Result<Self> {
+        Self::decode_value(r)
+    }
+}
+
+impl ValueCodec for () {
+    fn encode_value<W: Write>(&self, _w: &mut W) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn decode_value<R: Read>(_r: &mut R) -> io::Result<Self> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounds::RightBound;
+    use crate::Span;
+
+    fn encode<K, V>(map: &SpanMap<K, V>) -> Vec<u8>
+    where
+        K: Clone + Ord + KeyCodec,
+        V: Clone + Ord + ValueCodec,
+    {
+        let mut buf = Vec::new();
+        map.encode(&mut buf).unwrap();
+        buf
+    }
+
+    fn decode<K, V>(buf: &[u8]) -> io::Result<SpanMap<K, V>>
+    where
+        K: Clone + Ord + KeyCodec,
+        V: Clone + Ord + ValueCodec,
+    {
+        SpanMap::decode(&mut &buf[..])
+    }
+
+    #[test]
+    fn test_varint() {
+        for n in [0, 1, 127, 128, 300, u64::MAX as u128, u128::MAX] {
+            let mut buf = Vec::new();
+            write_varint(&mut buf, n).unwrap();
+            assert_eq!(read_varint(&mut buf.as_slice()).unwrap(), n);
+        }
+
+        let mut buf = Vec::new();
+        write_varint(&mut buf, 300).unwrap();
+        assert_eq!(buf, vec![0xac, 0x02]);
+
+        let too_long = [0xff; 20];
+        let err = read_varint(&mut &too_long[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut map = SpanMap::<i64, String>::new();
+        assert_eq!(decode::<i64, String>(&encode(&map)).unwrap(), map);
+
+        map.insert(..=-5, "a".to_string());
+        map.insert(-3..7, "b".to_string());
+        map.insert(i64::MAX.., "c".to_string());
+        map.insert_span(
+            Span::new(LeftBound::Excluded(i64::MIN), RightBound::Included(0)),
+            "d".to_string(),
+        );
+
+        assert_eq!(decode::<i64, String>(&encode(&map)).unwrap(), map);
+
+        let mut map = SpanMap::<u8, ()>::new();
+        map.insert(0..=255, ());
+        assert_eq!(decode::<u8, ()>(&encode(&map)).unwrap(), map);
+
+        let mut map = SpanMap::<String, i32>::new();
+        map.insert("a".to_string().."m".to_string(), -1);
+        map.insert("g".to_string().., 1);
+        assert_eq!(decode::<String, i32>(&encode(&map)).unwrap(), map);
+    }
+
+    #[test]
+    fn test_compact() {
+        // Repeated sets are stored once; consecutive keys cost one byte each.
+        let mut map = SpanMap::<u64, String>::new();
+        for i in 0..1000u64 {
+            map.insert(
+                1_000_000_000 + i * 10..1_000_000_000 + i * 10 + 5,
+                "x".to_string(),
+            );
+        }
+
+        let buf = encode(&map);
+        assert_eq!(decode::<u64, String>(&buf).unwrap(), map);
+        assert!(buf.len() < 2000 * 3 + 32, "{}", buf.len());
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        let mut map = SpanMap::<u32, u32>::new();
+        map.insert(1..5, 1);
+        let buf = encode(&map);
+
+        let mut bad = buf.clone();
+        bad[0] = b'X';
+        let err = decode::<u32, u32>(&bad).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let mut bad = buf.clone();
+        bad[4] = 2;
+        let err = decode::<u32, u32>(&bad).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("version"));
+
+        let err = decode::<u32, u32>(&buf[..buf.len() - 1]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+        // A set index past the dictionary
+        let mut bad = buf.clone();
+        *bad.last_mut().unwrap() = 9;
+        let err = decode::<u32, u32>(&bad).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // Keys too large for the key type
+        let err = decode::<u8, u32>(&encode(&{
+            let mut m = SpanMap::<u32, u32>::new();
+            m.insert(300..301, 1);
+            m
+        }))
+        .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_decode_non_canonical() {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(MAGIC);
+        buf.push(VERSION);
+        // Sets: {}, {7}
+        buf.extend_from_slice(&[2, 0, 1, 7]);
+
+        // Boundaries not in increasing order: Excluded(3) then Included(3)
+        let mut bad = buf.clone();
+        bad.extend_from_slice(&[3, TAG_UNBOUNDED, 0, TAG_EXCLUDED, 3, 1, TAG_INCLUDED, 0, 0]);
+        let err = decode::<u32, u32>(&bad).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // No boundaries at all
+        let mut bad = buf.clone();
+        bad.push(0);
+        let err = decode::<u32, u32>(&bad).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // Neighbours with the same set are merged
+        let mut ok = buf.clone();
+        ok.extend_from_slice(&[4, TAG_UNBOUNDED, 0, TAG_INCLUDED, 1, 1, TAG_INCLUDED, 2, 1]);
+        ok.extend_from_slice(&[TAG_INCLUDED, 2, 0]);
+        let mut want = SpanMap::new();
+        want.insert(1..5, 7);
+        assert_eq!(decode::<u32, u32>(&ok).unwrap(), want);
+    }
+}
@@ -0,0 +1,567 @@
+//! Reading and writing span assignments as CSV rows of `start, end, value`.
+//!
+//! The position of the columns, the delimiter, an optional header row and whether the start and
+//! the end of a row are inclusive are configured with a [`CsvFormat`]. An empty start or end
+//! field means the span is unbounded on that side.
+//!
+//! Fields may be double-quoted, with `""` standing for a quote inside a quoted field. Quoted
+//! fields can not span several lines.
+//!
+//! # Examples
+//! ```
+//! # use span_map::csv::CsvFormat;
+//! # use span_map::SpanMap;
+//!
+//! let format = CsvFormat {
+//!     has_header: true,
+//!     ..CsvFormat::default()
+//! };
+//!
+//! let input = "start,end,team\n0,100,infra\n50,,web\n";
+//! let map: SpanMap<u32, String> = format.read(input.as_bytes()).unwrap();
+//!
+//! assert_eq!(map.get(&70).collect::<Vec<_>>(), vec!["infra", "web"]);
+//!
+//! let mut out = Vec::new();
+//! format.write_segments(&mut out, &map).unwrap();
+//! assert_eq!(
+//!     String::from_utf8(out).unwrap(),
+//!     "start,end,value\n0,50,infra\n50,100,infra\n50,100,web\n100,,web\n"
+//! );
+//! ```
+
+use std::collections::BTreeSet;
+use std::fmt::Display;
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+use std::str::FromStr;
+
+use crate::bounds::LeftBound;
+use crate::bounds::RightBound;
+use crate::Span;
+use crate::SpanMap;
+use crate::Step;
+
+/// The layout of the CSV rows read and written by this module.
+///
+/// The default is `start,end,value` separated by commas, without a header row, with an inclusive
+/// start and an exclusive end, like `start..end`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvFormat {
+    /// The character separating the fields of a row.
+    pub delimiter: char,
+
+    /// Whether the first row is a header: it is skipped when reading and written when writing.
+    pub has_header: bool,
+
+    /// The 0-based column of the start of the span.
+    pub start_column: usize,
+
+    /// The 0-based column of the end of the span.
+    pub end_column: usize,
+
+    /// The 0-based column of the value.
+    pub value_column: usize,
+
+    /// Whether the start key belongs to the span.
+    pub start_inclusive: bool,
+
+    /// Whether the end key belongs to the span.
+    pub end_inclusive: bool,
+}
+
+impl Default for CsvFormat {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            has_header: false,
+            start_column: 0,
+            end_column: 1,
+            value_column: 2,
+            start_inclusive: true,
+            end_inclusive: false,
+        }
+    }
+}
+
+impl CsvFormat {
+    /// Reads every row into a new map, adding the value of the row to its span.
+    ///
+    /// Blank lines are skipped. Columns not used by the format are ignored.
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidData`] for a row that misses a column,
+    /// has a key or a value that can not be parsed, or whose span contains no key.
+    pub fn read<K, V, R>(&self, r: R) -> io::Result<SpanMap<K, V>>
+    where
+        K: Clone + Ord + FromStr,
+        K::Err: Display,
+        V: Clone + Ord + FromStr,
+        V::Err: Display,
+        R: BufRead,
+    {
+        let mut map = SpanMap::new();
+
+        for (i, line) in r.lines().enumerate() {
+            let line = line?;
+            if (i == 0 && self.has_header) || line.trim().is_empty() {
+                continue;
+            }
+
+            let invalid = |msg: String| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: {}", i + 1, msg),
+                )
+            };
+
+            let fields = split_row(&line, self.delimiter).map_err(invalid)?;
+            let field = |column: usize, name: &str| {
+                fields
+                    .get(column)
+                    .ok_or_else(|| invalid(format!("missing {} column {}", name, column)))
+            };
+
+            let start = field(self.start_column, "start")?;
+            let end = field(self.end_column, "end")?;
+            let value = field(self.value_column, "value")?;
+
+            let parse_key = |s: &str, name: &str| {
+                s.parse::<K>()
+                    .map_err(|e| invalid(format!("invalid {} {:?}: {}", name, s, e)))
+            };
+
+            let left = match start.as_str() {
+                "" => LeftBound::Unbounded,
+                s if self.start_inclusive => LeftBound::Included(parse_key(s, "start")?),
+                s => LeftBound::Excluded(parse_key(s, "start")?),
+            };
+            let right = match end.as_str() {
+                "" => RightBound::Unbounded,
+                s if self.end_inclusive => RightBound::Included(parse_key(s, "end")?),
+                s => RightBound::Excluded(parse_key(s, "end")?),
+            };
+            let value = value
+                .parse::<V>()
+                .map_err(|e| invalid(format!("invalid value {:?}: {}", value, e)))?;
+
+            let span = Span::new(left, right);
+            span.validate()
+                .map_err(|e| invalid(format!("{:?} to {:?}: {}", start, end, e)))?;
+
+            map.insert_span(span, value);
+        }
+
+        Ok(map)
+    }
+
+    /// Writes one row for every value of every non-empty segment of the map, in key order.
+    ///
+    /// The map is written in canonical form, see [`SpanMap::canonicalize`]. Bounds are converted
+    /// to the inclusivity of the format, e.g., with an exclusive end the segment `[1, 4]` is
+    /// written as `1,5`. A segment that contains no key of `K`, such as `..0` for `u8`, is not
+    /// written.
+    pub fn write_segments<K, V, W>(&self, w: &mut W, map: &SpanMap<K, V>) -> io::Result<()>
+    where
+        K: Step + Display,
+        V: Clone + Ord + Display,
+        W: Write,
+    {
+        self.write_header(w)?;
+
+        let mut map = map.clone();
+        map.canonicalize();
+
+        for (span, values) in &map {
+            for value in values {
+                self.write_row(w, &span, value)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes one row for every maximal span of every value of the map, ordered by value.
+    ///
+    /// Unlike [`CsvFormat::write_segments`], a value is written once for a span over which it
+    /// is present, however many other values start or end inside that span. As there, the map
+    /// is written in canonical form.
+    ///
+    /// # Examples
+    /// ```
+    /// # use span_map::csv::CsvFormat;
+    /// # use span_map::SpanMap;
+    ///
+    /// let mut map = SpanMap::new();
+    /// map.insert(0..100, "infra");
+    /// map.insert(50..60, "web");
+    ///
+    /// let mut out = Vec::new();
+    /// CsvFormat::default().write_spans(&mut out, &map).unwrap();
+    /// assert_eq!(String::from_utf8(out).unwrap(), "0,100,infra\n50,60,web\n");
+    /// ```
+    pub fn write_spans<K, V, W>(&self, w: &mut W, map: &SpanMap<K, V>) -> io::Result<()>
+    where
+        K: Step + Display,
+        V: Clone + Ord + Display,
+        W: Write,
+    {
+        self.write_header(w)?;
+
+        let mut map = map.clone();
+        map.canonicalize();
+
+        let values: BTreeSet<&V> = map.m.values().flatten().collect();
+        for value in values {
+            for span in map.spans_of(value).iter() {
+                self.write_row(w, &span, value)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_header<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        if self.has_header {
+            self.write_fields(w, "start", "end", "value")?;
+        }
+        Ok(())
+    }
+
+    fn write_row<K, V, W>(&self, w: &mut W, span: &Span<K>, value: &V) -> io::Result<()>
+    where
+        K: Step + Display,
+        V: Display,
+        W: Write,
+    {
+        // A segment past the end of the key domain, such as `..0` for `u8`, can not be written
+        // as a row that reads back.
+        if span.has_no_key() {
+            return Ok(());
+        }
+
+        // A bound that can not be moved to the other inclusivity is at the end of the key
+        // domain, i.e., it is the same as unbounded.
+        let start = match span.left() {
+            LeftBound::Unbounded => None,
+            LeftBound::Included(k) if self.start_inclusive => Some(k.clone()),
+            LeftBound::Included(k) => k.predecessor(),
+            LeftBound::Excluded(k) if self.start_inclusive => k.successor(),
+            LeftBound::Excluded(k) => Some(k.clone()),
+        };
+        let end = match span.right() {
+            RightBound::Unbounded => None,
+            RightBound::Included(k) if self.end_inclusive => Some(k.clone()),
+            RightBound::Included(k) => k.successor(),
+            RightBound::Excluded(k) if self.end_inclusive => k.predecessor(),
+            RightBound::Excluded(k) => Some(k.clone()),
+        };
+
+        let to_field = |k: Option<K>| k.map(|k| k.to_string()).unwrap_or_default();
+        self.write_fields(w, &to_field(start), &to_field(end), &value.to_string())
+    }
+
+    fn write_fields<W: Write>(
+        &self,
+        w: &mut W,
+        start: &str,
+        end: &str,
+        value: &str,
+    ) -> io::Result<()> {
+        let width = self
+            .start_column
+            .max(self.end_column)
+            .max(self.value_column)
+            + 1;
+
+        let mut row = vec![String::new(); width];
+        row[self.start_column] = quote(start, self.delimiter);
+        row[self.end_column] = quote(end, self.delimiter);
+        row[self.value_column] = quote(value, self.delimiter);
+
+        writeln!(w, "{}", row.join(&self.delimiter.to_string()))
+    }
+}
+
+/// Splits a row into fields, unquoting quoted ones and trimming unquoted ones.
+fn split_row(line: &str, delimiter: char) -> Result<Vec<String>, String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut quoted = false;
+
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            if c != '"' {
+                field.push(c);
+            } else if chars.next_if_eq(&'"').is_some() {
+                field.push('"');
+            } else {
+                in_quotes = false;
+            }
+        } else if c == '"' && !quoted && field.trim().is_empty() {
+            field.clear();
+            in_quotes = true;
+            quoted = true;
+        } else if c == delimiter {
+            fields.push(finish_field(field, quoted));
+            field = String::new();
+            quoted = false;
+        } else if quoted && c.is_whitespace() {
+            // Padding after the closing quote
+        } else {
+            field.push(c);
+        }
+    }
+
+    if in_quotes {
+        return Err("unterminated quoted field".to_string());
+    }
+
+    fields.push(finish_field(field, quoted));
+    Ok(fields)
+}
+
+fn finish_field(field: String, quoted: bool) -> String {
+    if quoted {
+        field
+    } else {
+        field.trim().to_string()
+    }
+}
+
+/// Quotes a field if it would not be read back as is.
+fn quote(field: &str, delimiter: char) -> String {
+    let needs_quotes = field.contains([delimiter, '"', '\n', '\r']) || field.trim() != field;
+
+    if needs_quotes {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_default() {
+        let input = "1,5,a\n\n3,,b\n,2,c\n";
+        let map: SpanMap<i32, String> = CsvFormat::default().read(input.as_bytes()).unwrap();
+
+        let mut want = SpanMap::new();
+        want.insert(1..5, "a".to_string());
+        want.insert(3.., "b".to_string());
+        want.insert(..2, "c".to_string());
+        assert_eq!(map, want);
+    }
+
+    #[test]
+    fn test_read_custom_format() {
+        let format = CsvFormat {
+            delimiter: ';',
+            has_header: true,
+            start_column: 1,
+            end_column: 2,
+            value_column: 0,
+            start_inclusive: false,
+            end_inclusive: true,
+        };
+
+        let input = "owner;from;to;comment\n\"ops; east\";10;20;ignored\n web ; 20 ; 30\n";
+        let map: SpanMap<u32, String> = format.read(input.as_bytes()).unwrap();
+
+        let mut want = SpanMap::new();
+        want.insert_span(
+            Span::new(LeftBound::Excluded(10), RightBound::Included(20)),
+            "ops; east".to_string(),
+        );
+        want.insert_span(
+            Span::new(LeftBound::Excluded(20), RightBound::Included(30)),
+            "web".to_string(),
+        );
+        assert_eq!(map, want);
+    }
+
+    #[test]
+    fn test_read_invalid() {
+        let format = CsvFormat::default();
+
+        let err = format
+            .read::<i32, String, _>("1,5\n".as_bytes())
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "line 1: missing value column 2");
+
+        let err = format
+            .read::<i32, String, _>("1,5,a\nx,5,a\n".as_bytes())
+            .unwrap_err();
+        assert!(
+            err.to_string().starts_with("line 2: invalid start \"x\""),
+            "{}",
+            err
+        );
+
+        let err = format
+            .read::<i32, i32, _>("1,5,a\n".as_bytes())
+            .unwrap_err();
+        assert!(
+            err.to_string().starts_with("line 1: invalid value \"a\""),
+            "{}",
+            err
+        );
+
+        let err = format
+            .read::<i32, String, _>("5,5,a\n".as_bytes())
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 1: \"5\" to \"5\": span contains no key"
+        );
+
+        let err = format
+            .read::<i32, String, _>("1,5,\"a\n".as_bytes())
+            .unwrap_err();
+        assert_eq!(err.to_string(), "line 1: unterminated quoted field");
+    }
+
+    #[test]
+    fn test_split_row() {
+        assert_eq!(split_row("", ',').unwrap(), vec![""]);
+        assert_eq!(split_row(" a , b,", ',').unwrap(), vec!["a", "b", ""]);
+        assert_eq!(
+            split_row(r#""a,b", "say ""hi""" ,c"#, ',').unwrap(),
+            vec!["a,b", r#"say "hi""#, "c"]
+        );
+        assert_eq!(split_row(r#"" padded ""#, ',').unwrap(), vec![" padded "]);
+    }
+
+    #[test]
+    fn test_write_segments() {
+        let mut map = SpanMap::<i32, String>::new();
+        map.insert(..=4, "a".to_string());
+        map.insert(2..8, "b, c".to_string());
+
+        let mut out = Vec::new();
+        CsvFormat::default().write_segments(&mut out, &map).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            ",2,a\n2,5,a\n2,5,\"b, c\"\n5,8,\"b, c\"\n"
+        );
+
+        let closed = CsvFormat {
+            delimiter: '\t',
+            end_inclusive: true,
+            ..CsvFormat::default()
+        };
+        let mut out = Vec::new();
+        closed.write_segments(&mut out, &map).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\t1\ta\n2\t4\ta\n2\t4\tb, c\n5\t7\tb, c\n"
+        );
+    }
+
+    #[test]
+    fn test_write_key_domain_ends() {
+        let mut map = SpanMap::<u8, String>::new();
+        map.insert(0..=255, "all".to_string());
+
+        // Neither 0 exclusive nor 255 exclusive can be expressed: both ends are unbounded
+        let open = CsvFormat {
+            start_inclusive: false,
+            ..CsvFormat::default()
+        };
+        let mut out = Vec::new();
+        open.write_spans(&mut out, &map).unwrap();
+        assert_eq!(String::from_utf8(out.clone()).unwrap(), ",,all\n");
+
+        let back: SpanMap<u8, String> = open.read(out.as_slice()).unwrap();
+        let mut want = SpanMap::new();
+        want.insert(.., "all".to_string());
+        assert_eq!(back, want);
+    }
+
+    #[test]
+    fn test_write_key_domain_ends_without_keys() {
+        let mut map = SpanMap::<u8, String>::new();
+        map.insert(..0, "none".to_string());
+        map.insert_span(
+            Span::new(LeftBound::Excluded(255), RightBound::Unbounded),
+            "none".to_string(),
+        );
+        map.insert(1..3, "some".to_string());
+
+        // Neither `..0` nor `(255, ..` contains a key: writing them as unbounded would widen them
+        let closed = CsvFormat {
+            end_inclusive: true,
+            ..CsvFormat::default()
+        };
+        let mut out = Vec::new();
+        closed.write_segments(&mut out, &map).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "1,2,some\n");
+
+        let mut out = Vec::new();
+        closed.write_spans(&mut out, &map).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "1,2,some\n");
+    }
+
+    #[test]
+    fn test_write_segments_between_adjacent_keys() {
+        // With inclusive ends, `(5, 6)` separates `[1, 5]` and `[6, 9]`: it contains no key and
+        // must not be written
+        let input = "1,5,a\n6,9,b\n1,9,c\n";
+
+        for end_inclusive in [true, false] {
+            let format = CsvFormat {
+                end_inclusive,
+                ..CsvFormat::default()
+            };
+
+            let mut map: SpanMap<u32, String> = format.read(input.as_bytes()).unwrap();
+
+            let mut out = Vec::new();
+            format.write_segments(&mut out, &map).unwrap();
+
+            let want = if end_inclusive {
+                "1,5,a\n1,5,c\n6,9,b\n6,9,c\n"
+            } else {
+                // `[5, 6)` holds the key 5
+                "1,5,a\n1,5,c\n5,6,c\n6,9,b\n6,9,c\n"
+            };
+            assert_eq!(String::from_utf8(out.clone()).unwrap(), want);
+
+            let mut back: SpanMap<u32, String> = format.read(out.as_slice()).unwrap();
+            back.canonicalize();
+            map.canonicalize();
+            assert_eq!(back, map);
+        }
+    }
+
+    #[test]
+    fn test_write_spans_round_trip() {
+        let format = CsvFormat {
+            has_header: true,
+            end_inclusive: true,
+            ..CsvFormat::default()
+        };
+
+        let input = "from,to,who\n1,10,alice\n5,20,bob\n11,15,alice\n";
+        let mut map: SpanMap<i64, String> = format.read(input.as_bytes()).unwrap();
+
+        // `[1, 10]` and `[11, 15]` are written as one span, as in the canonical map
+        let mut out = Vec::new();
+        format.write_spans(&mut out, &map).unwrap();
+        assert_eq!(
+            String::from_utf8(out.clone()).unwrap(),
+            "start,end,value\n1,15,alice\n5,20,bob\n"
+        );
+
+        let mut back: SpanMap<i64, String> = format.read(out.as_slice()).unwrap();
+        back.canonicalize();
+        map.canonicalize();
+        assert_eq!(back, map);
+    }
+}
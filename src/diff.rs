@@ -0,0 +1,197 @@
+//! Differences between two [`SpanMap`]s.
+
+use std::collections::BTreeSet;
+
+use crate::bounds::LeftBound;
+use crate::bounds::RightBound;
+use crate::Span;
+use crate::SpanMap;
+
+impl<K, V> SpanMap<K, V>
+where
+    K: Clone + Ord,
+    V: Clone + Ord,
+{
+    /// Returns the changes that turn `old` into `new`, as `(span, added, removed)` for every
+    /// region where the value sets differ, in key order.
+    ///
+    /// `added` holds the values present in `new` but not in `old` over `span`, and `removed`
+    /// those present in `old` but not in `new`. Neighbouring regions with the same changes are
+    /// reported as one.
+    ///
+    /// Both boundary sequences are walked once, in `O(n + m)` set comparisons.
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::collections::BTreeSet;
+    /// # use span_map::Span;
+    /// # use span_map::SpanMap;
+    ///
+    /// let mut old = SpanMap::new();
+    /// old.insert(0..10, "a");
+    ///
+    /// let mut new = old.clone();
+    /// new.remove(0..5, "a");
+    /// new.insert(8..12, "b");
+    ///
+    /// assert_eq!(
+    ///     SpanMap::diff(&old, &new),
+    ///     vec![
+    ///         (
+    ///             Span::from_range(0..5),
+    ///             BTreeSet::new(),
+    ///             BTreeSet::from(["a"])
+    ///         ),
+    ///         (
+    ///             Span::from_range(8..12),
+    ///             BTreeSet::from(["b"]),
+    ///             BTreeSet::new()
+    ///         ),
+    ///     ]
+    /// );
+    /// ```
+    pub fn diff(old: &Self, new: &Self) -> Vec<(Span<K>, BTreeSet<V>, BTreeSet<V>)> {
+        let mut out: Vec<(Span<K>, BTreeSet<V>, BTreeSet<V>)> = Vec::new();
+
+        let mut old_it = old.m.iter().peekable();
+        let mut new_it = new.m.iter().peekable();
+
+        // Both maps always start with an Unbounded boundary.
+        let mut old_set = &old.m[&LeftBound::Unbounded];
+        let mut new_set = &new.m[&LeftBound::Unbounded];
+
+        // The changes of the region starting at `bound`, which ends at the next boundary.
+        let mut pending: Option<(LeftBound<K>, BTreeSet<V>, BTreeSet<V>)> = None;
+
+        loop {
+            let bound = match (old_it.peek(), new_it.peek()) {
+                (None, None) => break,
+                (Some((ob, _)), None) => *ob,
+                (None, Some((nb, _))) => *nb,
+                (Some((ob, _)), Some((nb, _))) => std::cmp::min(*ob, *nb),
+            };
+
+            if let Some((_, set)) = old_it.next_if(|(b, _)| *b == bound) {
+                old_set = set;
+            }
+            if let Some((_, set)) = new_it.next_if(|(b, _)| *b == bound) {
+                new_set = set;
+            }
+
+            let added: BTreeSet<V> = new_set.difference(old_set).cloned().collect();
+            let removed: BTreeSet<V> = old_set.difference(new_set).cloned().collect();
+
+            if let Some((_, pending_added, pending_removed)) = &pending {
+                if *pending_added == added && *pending_removed == removed {
+                    continue;
+                }
+            }
+
+            if let Some((left, a, r)) = pending.take() {
+                // Safe unwrap(): `bound` is after `left`, so it is not Unbounded
+                out.push((Span::new(left, bound.adjacent_right().unwrap()), a, r));
+            }
+
+            if !added.is_empty() || !removed.is_empty() {
+                pending = Some((bound.clone(), added, removed));
+            }
+        }
+
+        if let Some((left, a, r)) = pending {
+            out.push((Span::new(left, RightBound::Unbounded), a, r));
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_rng::TestRng;
+
+    /// Applies the result of `diff` to `old`.
+    fn apply(
+        old: &SpanMap<i32, u8>,
+        diff: &[(Span<i32>, BTreeSet<u8>, BTreeSet<u8>)],
+    ) -> SpanMap<i32, u8> {
+        let mut map = old.clone();
+        for (span, added, removed) in diff {
+            for v in added {
+                map.insert_span(span.clone(), *v);
+            }
+            for v in removed {
+                map.remove_span(span.clone(), *v);
+            }
+        }
+        map
+    }
+
+    #[test]
+    fn test_diff_equal_maps() {
+        let mut map = SpanMap::<i32, u8>::new();
+        assert_eq!(SpanMap::diff(&map, &map), vec![]);
+
+        map.insert(1..5, 1);
+        map.insert(3.., 2);
+        assert_eq!(SpanMap::diff(&map, &map.clone()), vec![]);
+    }
+
+    #[test]
+    fn test_diff_unbounded_and_merged() {
+        let old = SpanMap::<i32, u8>::new();
+        let mut new = SpanMap::new();
+        new.insert(..0, 1);
+        new.insert(0..5, 2);
+        new.insert(5.., 1);
+
+        assert_eq!(
+            SpanMap::diff(&old, &new),
+            vec![
+                (Span::from_range(..0), BTreeSet::from([1]), BTreeSet::new()),
+                (Span::from_range(0..5), BTreeSet::from([2]), BTreeSet::new()),
+                (Span::from_range(5..), BTreeSet::from([1]), BTreeSet::new()),
+            ]
+        );
+
+        // Boundaries of `old` inside a region with the same changes do not split it
+        let mut old = SpanMap::new();
+        old.insert(0..2, 3);
+        old.insert(2..4, 4);
+        let new = SpanMap::new();
+        let mut both = old.clone();
+        both.insert(0..4, 5);
+
+        assert_eq!(
+            SpanMap::diff(&old, &both),
+            vec![(Span::from_range(0..4), BTreeSet::from([5]), BTreeSet::new())]
+        );
+        assert_eq!(SpanMap::diff(&old, &new).len(), 2);
+    }
+
+    #[test]
+    fn test_diff_applies() {
+        let mut rng = TestRng::new(11);
+        let mut next = move |n| rng.below(n) as i32;
+
+        for _ in 0..100 {
+            let mut old = SpanMap::new();
+            let mut new = SpanMap::new();
+            for _ in 0..6 {
+                let (a, b) = (next(20), next(20));
+                old.insert(a..b, next(3) as u8);
+                let (a, b) = (next(20), next(20));
+                new.insert(a..b, next(3) as u8);
+            }
+
+            let diff = SpanMap::diff(&old, &new);
+            assert_eq!(apply(&old, &diff), new);
+
+            for (span, added, removed) in &diff {
+                assert!(!span.is_empty());
+                assert!(added.is_disjoint(removed));
+                assert!(!added.is_empty() || !removed.is_empty());
+            }
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! Formatting of [`SpanMap`] and [`SpanSet`] in interval notation.
+
+use std::fmt;
+use std::fmt::Formatter;
+
+use crate::bounds::LeftBound;
+use crate::bounds::RightBound;
+use crate::Span;
+use crate::SpanMap;
+use crate::SpanSet;
+
+/// Formats every non-empty segment as `[1, 5) => {a, b}`.
+///
+/// The alternate form `{:#}` puts every segment on its own line.
+///
+/// # Examples
+/// ```
+/// # use span_map::SpanMap;
+///
+/// let mut map = SpanMap::new();
+/// map.insert(1..5, "a");
+/// map.insert(3..7, "b");
+///
+/// assert_eq!(
+///     map.to_string(),
+///     "{[1, 3) => {a}, [3, 5) => {a, b}, [5, 7) => {b}}"
+/// );
+/// assert_eq!(
+///     format!("{:#}", map),
+///     "{\n    [1, 3) => {a},\n    [3, 5) => {a, b},\n    [5, 7) => {b},\n}"
+/// );
+/// ```
+impl<K, V> fmt::Display for SpanMap<K, V>
+where
+    K: Clone + Ord + fmt::Display,
+    V: Clone + Ord + fmt::Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_segments(
+            f,
+            self,
+            |f, span| write!(f, "{}", span),
+            |f, v| write!(f, "{}", v),
+        )
+    }
+}
+
+/// Same as the `Display` implementation, with keys and values formatted with `Debug`.
+impl<K, V> fmt::Debug for SpanMap<K, V>
+where
+    K: Clone + Ord + fmt::Debug,
+    V: Clone + Ord + fmt::Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_segments(f, self, write_span_debug, |f, v| write!(f, "{:?}", v))
+    }
+}
+
+/// Formats the set as the list of its spans, e.g., `{[1, 5), [7, 9]}`.
+impl<K> fmt::Debug for SpanSet<K>
+where
+    K: Clone + Ord + fmt::Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        struct DebugSpan<K: Ord>(Span<K>);
+
+        impl<K> fmt::Debug for DebugSpan<K>
+        where
+            K: Ord + fmt::Debug,
+        {
+            fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                write_span_debug(f, &self.0)
+            }
+        }
+
+        f.debug_set().entries(self.iter().map(DebugSpan)).finish()
+    }
+}
+
+fn write_segments<K, V>(
+    f: &mut Formatter<'_>,
+    map: &SpanMap<K, V>,
+    write_span: impl Fn(&mut Formatter<'_>, &Span<K>) -> fmt::Result,
+    write_value: impl Fn(&mut Formatter<'_>, &V) -> fmt::Result,
+) -> fmt::Result
+where
+    K: Clone + Ord,
+    V: Clone + Ord,
+{
+    let alternate = f.alternate();
+
+    let mut segments = map.iter().peekable();
+
+    write!(f, "{{")?;
+    if alternate && segments.peek().is_some() {
+        writeln!(f)?;
+    }
+
+    for (i, (span, set)) in segments.enumerate() {
+        if alternate {
+            write!(f, "    ")?;
+        } else if i > 0 {
+            write!(f, ", ")?;
+        }
+
+        write_span(f, &span)?;
+        write!(f, " => {{")?;
+        for (j, v) in set.iter().enumerate() {
+            if j > 0 {
+                write!(f, ", ")?;
+            }
+            write_value(f, v)?;
+        }
+        write!(f, "}}")?;
+
+        if alternate {
+            writeln!(f, ",")?;
+        }
+    }
+
+    write!(f, "}}")
+}
+
+/// Writes a span in interval notation, with keys formatted with `Debug`.
+fn write_span_debug<K>(f: &mut Formatter<'_>, span: &Span<K>) -> fmt::Result
+where
+    K: Ord + fmt::Debug,
+{
+    match span.left() {
+        LeftBound::Unbounded => write!(f, "(-∞")?,
+        LeftBound::Included(k) => write!(f, "[{:?}", k)?,
+        LeftBound::Excluded(k) => write!(f, "({:?}", k)?,
+    }
+
+    write!(f, ", ")?;
+
+    match span.right() {
+        RightBound::Excluded(k) => write!(f, "{:?})", k),
+        RightBound::Included(k) => write!(f, "{:?}]", k),
+        RightBound::Unbounded => write!(f, "∞)"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let mut map = SpanMap::<i32, &str>::new();
+        assert_eq!(map.to_string(), "{}");
+        assert_eq!(format!("{:#}", map), "{}");
+
+        map.insert(..=1, "a");
+        map.insert(3..7, "b");
+        map.insert(5.., "c");
+
+        assert_eq!(
+            map.to_string(),
+            "{(-∞, 1] => {a}, [3, 5) => {b}, [5, 7) => {b, c}, [7, ∞) => {c}}"
+        );
+        assert_eq!(
+            format!("{:#}", map),
+            [
+                "{",
+                "    (-∞, 1] => {a},",
+                "    [3, 5) => {b},",
+                "    [5, 7) => {b, c},",
+                "    [7, ∞) => {c},",
+                "}",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_debug() {
+        let mut map = SpanMap::<&str, &str>::new();
+        assert_eq!(format!("{:?}", map), "{}");
+
+        map.insert("a"..="c", "x");
+        map.insert("b".., "y");
+
+        assert_eq!(
+            format!("{:?}", map),
+            r#"{["a", "b") => {"x"}, ["b", "c"] => {"x", "y"}, ("c", ∞) => {"y"}}"#
+        );
+        assert_eq!(
+            format!("{:#?}", map),
+            [
+                "{",
+                r#"    ["a", "b") => {"x"},"#,
+                r#"    ["b", "c"] => {"x", "y"},"#,
+                r#"    ("c", ∞) => {"y"},"#,
+                "}",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_span_set_debug() {
+        let mut set = SpanSet::<i32>::new();
+        assert_eq!(format!("{:?}", set), "{}");
+
+        set.insert(1..5);
+        set.insert(7..=9);
+
+        assert_eq!(format!("{:?}", set), "{[1, 5), [7, 9]}");
+    }
+}
@@ -0,0 +1,25 @@
+use std::error::Error;
+use std::fmt;
+
+/// The error returned when a span that contains no key is used where a non-empty one is
+/// required.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpanError {
+    /// The start of the span is greater than its end, e.g., `5..1`.
+    Inverted,
+
+    /// The start and the end of the span are the same key, but at least one of them is
+    /// excluded, e.g., `5..5`.
+    Empty,
+}
+
+impl fmt::Display for SpanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpanError::Inverted => write!(f, "span start is greater than span end"),
+            SpanError::Empty => write!(f, "span contains no key"),
+        }
+    }
+}
+
+impl Error for SpanError {}
@@ -0,0 +1,247 @@
+//! Iteration over the segments of a [`SpanMap`], and the std collection traits.
+
+use std::collections::btree_map;
+use std::collections::BTreeSet;
+use std::iter::FusedIterator;
+use std::iter::Peekable;
+use std::ops::RangeBounds;
+
+use crate::bounds::LeftBound;
+use crate::bounds::RightBound;
+use crate::Span;
+use crate::SpanMap;
+
+/// An iterator over the non-empty segments of a [`SpanMap`], created by [`SpanMap::iter`].
+#[derive(Debug, Clone)]
+pub struct Iter<'a, K, V> {
+    inner: Peekable<btree_map::Iter<'a, LeftBound<K>, BTreeSet<V>>>,
+}
+
+/// An owning iterator over the non-empty segments of a [`SpanMap`], created by
+/// [`SpanMap::into_iter`](IntoIterator::into_iter).
+#[derive(Debug)]
+pub struct IntoIter<K, V> {
+    inner: Peekable<btree_map::IntoIter<LeftBound<K>, BTreeSet<V>>>,
+}
+
+/// Returns the right bound of the segment that ends where `next` starts.
+fn right_of<K: Clone>(next: Option<&LeftBound<K>>) -> RightBound<K> {
+    match next {
+        // Safe unwrap(): only the first boundary is Unbounded
+        Some(next) => next.adjacent_right().unwrap(),
+        None => RightBound::Unbounded,
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V>
+where
+    K: Clone + Ord,
+{
+    type Item = (Span<K>, &'a BTreeSet<V>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (left, set) = self.inner.next()?;
+            if set.is_empty() {
+                continue;
+            }
+
+            let right = right_of(self.inner.peek().map(|(next, _set)| *next));
+            return Some((Span::new(left.clone(), right), set));
+        }
+    }
+}
+
+impl<K, V> FusedIterator for Iter<'_, K, V> where K: Clone + Ord {}
+
+impl<K, V> Iterator for IntoIter<K, V>
+where
+    K: Clone + Ord,
+{
+    type Item = (Span<K>, BTreeSet<V>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (left, set) = self.inner.next()?;
+            if set.is_empty() {
+                continue;
+            }
+
+            let right = right_of(self.inner.peek().map(|(next, _set)| next));
+            return Some((Span::new(left, right), set));
+        }
+    }
+}
+
+impl<K, V> FusedIterator for IntoIter<K, V> where K: Clone + Ord {}
+
+impl<K, V> SpanMap<K, V>
+where
+    K: Clone + Ord,
+    V: Clone + Ord,
+{
+    /// Returns an iterator over the non-empty segments of the map, in key order.
+    ///
+    /// A segment is a maximal span over which the set of values does not change. Neighbouring
+    /// segments always have different value sets.
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::collections::BTreeSet;
+    /// # use span_map::Span;
+    /// # use span_map::SpanMap;
+    ///
+    /// let mut map = SpanMap::new();
+    /// map.insert(1..5, "a");
+    /// map.insert(3..7, "b");
+    ///
+    /// let segments: Vec<_> = map.iter().collect();
+    /// assert_eq!(
+    ///     segments,
+    ///     vec![
+    ///         (Span::from_range(1..3), &BTreeSet::from(["a"])),
+    ///         (Span::from_range(3..5), &BTreeSet::from(["a", "b"])),
+    ///         (Span::from_range(5..7), &BTreeSet::from(["b"])),
+    ///     ]
+    /// );
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.m.iter().peekable(),
+        }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a SpanMap<K, V>
+where
+    K: Clone + Ord,
+    V: Clone + Ord,
+{
+    type Item = (Span<K>, &'a BTreeSet<V>);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K, V> IntoIterator for SpanMap<K, V>
+where
+    K: Clone + Ord,
+    V: Clone + Ord,
+{
+    type Item = (Span<K>, BTreeSet<V>);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            inner: self.m.into_iter().peekable(),
+        }
+    }
+}
+
+/// Inserts every value into its range, as [`SpanMap::insert`] does.
+///
+/// The new values are first collected into a map with [`SpanMap::from_spans`], which is then
+/// merged into this one.
+impl<K, V, R> Extend<(R, V)> for SpanMap<K, V>
+where
+    K: Clone + Ord,
+    V: Clone + Ord,
+    R: RangeBounds<K>,
+{
+    fn extend<I: IntoIterator<Item = (R, V)>>(&mut self, iter: I) {
+        let other = SpanMap::from_iter(iter);
+        self.union_with(&other);
+    }
+}
+
+/// Builds a map from `(range, value)` pairs with [`SpanMap::from_spans`].
+///
+/// # Examples
+/// ```
+/// # use span_map::SpanMap;
+///
+/// let map: SpanMap<i32, &str> = [(1..5, "a"), (3..7, "b")].into_iter().collect();
+/// assert_eq!(map.get(&4).copied().collect::<Vec<_>>(), vec!["a", "b"]);
+/// ```
+impl<K, V, R> FromIterator<(R, V)> for SpanMap<K, V>
+where
+    K: Clone + Ord,
+    V: Clone + Ord,
+    R: RangeBounds<K>,
+{
+    fn from_iter<I: IntoIterator<Item = (R, V)>>(iter: I) -> Self {
+        SpanMap::from_spans(
+            iter.into_iter()
+                .map(|(range, value)| (Span::from_range(range), value)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iter() {
+        let mut map = SpanMap::<i32, &str>::new();
+        assert_eq!(map.iter().next(), None);
+
+        map.insert(..1, "a");
+        map.insert(3..=5, "b");
+        map.insert(5.., "c");
+
+        let segments: Vec<_> = map.iter().collect();
+        assert_eq!(
+            segments,
+            vec![
+                (Span::from_range(..1), &BTreeSet::from(["a"])),
+                (Span::from_range(3..5), &BTreeSet::from(["b"])),
+                (Span::from_range(5..=5), &BTreeSet::from(["b", "c"])),
+                (
+                    Span::new(LeftBound::Excluded(5), RightBound::Unbounded),
+                    &BTreeSet::from(["c"])
+                ),
+            ]
+        );
+
+        let borrowed: Vec<_> = (&map).into_iter().map(|(span, _set)| span).collect();
+        let owned: Vec<_> = map.clone().into_iter().collect();
+
+        assert_eq!(owned.len(), 4);
+        for ((span, set), (borrowed_span, borrowed_set)) in owned.iter().zip(map.iter()) {
+            assert_eq!(span, &borrowed_span);
+            assert_eq!(set, borrowed_set);
+        }
+        assert_eq!(
+            borrowed,
+            owned
+                .into_iter()
+                .map(|(span, _set)| span)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_from_iter_and_extend() {
+        let mut map: SpanMap<i32, &str> = vec![(1..5, "a"), (3..7, "b")].into_iter().collect();
+
+        let mut want = SpanMap::new();
+        want.insert(1..5, "a");
+        want.insert(3..7, "b");
+        assert_eq!(map, want);
+
+        map.extend([(Span::from_range(..=0), "c"), (Span::from_range(7..9), "b")]);
+        want.insert(..=0, "c");
+        want.insert(7..9, "b");
+        assert_eq!(map, want);
+
+        // Round trip through the owning iterator
+        let mut rebuilt = SpanMap::new();
+        for (span, set) in map.clone() {
+            rebuilt.extend(set.into_iter().map(|v| (span.clone(), v)));
+        }
+        assert_eq!(rebuilt, map);
+    }
+}
@@ -21,17 +21,57 @@
 //! assert_eq!(values, vec!["value1", "value2"]);
 //! ```
 
-#[doc(hidden)]
+mod batch;
+pub mod bed;
 pub mod bounds;
-#[doc(hidden)]
+mod change;
+mod codec;
+pub mod csv;
+mod diff;
+mod display;
+mod error;
+mod iter;
+mod mapped;
+mod parse;
+mod patch;
+mod render;
+#[cfg(feature = "serde")]
+mod serde_impl;
 pub mod span;
+mod span_set;
+mod splice;
+mod step;
+mod sweep;
+#[cfg(test)]
+mod test_rng;
+mod transaction;
 
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
+use std::ops::Add;
 use std::ops::RangeBounds;
 
+pub use batch::Batch;
 use bounds::LeftBound;
-use span::Span;
+pub use change::Change;
+pub use change::ChangeKind;
+pub use codec::KeyCodec;
+pub use codec::ValueCodec;
+pub use error::SpanError;
+pub use iter::IntoIter;
+pub use iter::Iter;
+pub use mapped::MappedSpanMap;
+pub use mapped::MappedValues;
+pub use parse::ParseSpanError;
+pub use parse::ParseSpanErrorKind;
+pub use patch::PatchOp;
+pub use patch::SpanPatch;
+pub use span::Span;
+pub use span_set::SpanSet;
+pub use splice::Gravity;
+pub use splice::Stickiness;
+pub use step::Step;
+pub use transaction::Transaction;
 
 /// A map that associates spans (ranges) with sets of values.
 ///
@@ -47,7 +87,7 @@ use span::Span;
 ///
 /// * `K`: The type of the keys defining span boundaries. Must implement `Clone` and `Ord`.
 /// * `V`: The type of values stored in the sets. Must implement `Clone` and `Ord`.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq)]
 pub struct SpanMap<K, V>
 where
     K: Clone + Ord,
@@ -102,7 +142,15 @@ where
 
     /// Inserts a value into all sets associated with spans overlapping the given range.
     ///
-    /// Adjacent ranges with the same value are merged into a single range.
+    /// Adjacent ranges with the same value set are merged into a single range, including ranges
+    /// inside `range` whose sets become identical.
+    ///
+    /// Ranges are compared by their bounds, not by the keys they contain. For discrete keys,
+    /// `insert(1..=5, v)` followed by `insert(6..=9, v)` leaves a `(5, 6)` range without `v`
+    /// between them, until [`SpanMap::canonicalize`] is called.
+    ///
+    /// An empty or inverted range, such as `5..5` or `5..1`, contains no key and leaves the map
+    /// unchanged. Use [`SpanMap::try_insert`] to reject such ranges instead.
     pub fn insert<R>(&mut self, range: R, value: V)
     where
         R: RangeBounds<K>,
@@ -112,7 +160,11 @@ where
 
     /// Removes a value from all sets associated with spans overlapping the given range.
     ///
-    /// Adjacent ranges with the same value are merged into a single range.
+    /// Adjacent ranges with the same value set are merged into a single range, including ranges
+    /// inside `range` whose sets become identical.
+    ///
+    /// An empty or inverted range, such as `5..5` or `5..1`, contains no key and leaves the map
+    /// unchanged. Use [`SpanMap::try_remove`] to reject such ranges instead.
     pub fn remove<R>(&mut self, range: R, value: V)
     where
         R: RangeBounds<K>,
@@ -120,23 +172,353 @@ where
         self.remove_span(Span::from_range(range), value);
     }
 
-    #[doc(hidden)]
+    /// Same as [`SpanMap::insert`], but returns an error if the range is empty or inverted.
+    ///
+    /// # Examples
+    /// ```
+    /// # use span_map::SpanMap;
+    /// # use span_map::SpanError;
+    ///
+    /// let mut map = SpanMap::new();
+    ///
+    /// assert_eq!(map.try_insert(1..5, "a"), Ok(()));
+    /// assert_eq!(map.try_insert(5..1, "a"), Err(SpanError::Inverted));
+    /// assert_eq!(map.try_insert(5..5, "a"), Err(SpanError::Empty));
+    /// ```
+    pub fn try_insert<R>(&mut self, range: R, value: V) -> Result<(), SpanError>
+    where
+        R: RangeBounds<K>,
+    {
+        let span = Span::from_range(range);
+        span.validate()?;
+        self.insert_span(span, value);
+        Ok(())
+    }
+
+    /// Same as [`SpanMap::remove`], but returns an error if the range is empty or inverted.
+    pub fn try_remove<R>(&mut self, range: R, value: V) -> Result<(), SpanError>
+    where
+        R: RangeBounds<K>,
+    {
+        let span = Span::from_range(range);
+        span.validate()?;
+        self.remove_span(span, value);
+        Ok(())
+    }
+
+    /// Splits the map into two at the given bound.
+    ///
+    /// Returns a new map containing everything at or after `bound`, and leaves everything before
+    /// `bound` in `self`. A segment that straddles `bound` is cut into two.
+    ///
+    /// # Examples
+    /// ```
+    /// # use span_map::SpanMap;
+    /// # use span_map::bounds::LeftBound;
+    ///
+    /// let mut map = SpanMap::new();
+    /// map.insert(0..10, "a");
+    ///
+    /// let right = map.split_off(&LeftBound::Included(5));
+    ///
+    /// assert_eq!(map.get(&4).count(), 1);
+    /// assert_eq!(map.get(&5).count(), 0);
+    /// assert_eq!(right.get(&4).count(), 0);
+    /// assert_eq!(right.get(&5).count(), 1);
+    /// ```
+    pub fn split_off(&mut self, bound: &LeftBound<K>) -> Self {
+        if *bound == LeftBound::Unbounded {
+            return std::mem::take(self);
+        }
+
+        self.ensure_boundary(bound.clone());
+
+        let mut m = self.m.split_off(bound);
+        m.insert(LeftBound::Unbounded, BTreeSet::new());
+
+        let mut right = Self { m };
+        right.merge_adjacent_left(bound.clone());
+
+        self.truncate(bound);
+        right
+    }
+
+    /// Removes everything at or after the given bound, keeping only what lies before it.
+    ///
+    /// # Examples
+    /// ```
+    /// # use span_map::SpanMap;
+    /// # use span_map::bounds::LeftBound;
+    ///
+    /// let mut map = SpanMap::new();
+    /// map.insert(0..10, "a");
+    ///
+    /// map.truncate(&LeftBound::Included(5));
+    ///
+    /// assert_eq!(map.get(&4).count(), 1);
+    /// assert_eq!(map.get(&5).count(), 0);
+    /// ```
+    pub fn truncate(&mut self, bound: &LeftBound<K>) {
+        if *bound == LeftBound::Unbounded {
+            *self = Self::new();
+            return;
+        }
+
+        self.m.split_off(bound);
+
+        // The segment straddling `bound`, if any, now extends to infinity; cut it at `bound`.
+        self.m.insert(bound.clone(), BTreeSet::new());
+        self.merge_adjacent_left(bound.clone());
+    }
+
+    /// Moves all values from `other` into `self`, leaving `other` empty.
+    ///
+    /// If the non-empty content of `other` lies entirely at or after that of `self`, the two
+    /// maps are concatenated in `O(n + m)` and merged at the seam. Otherwise the value sets of
+    /// `other` are unioned into `self`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use span_map::SpanMap;
+    /// # use span_map::bounds::LeftBound;
+    ///
+    /// let mut map = SpanMap::new();
+    /// map.insert(0..10, "a");
+    ///
+    /// let mut right = map.split_off(&LeftBound::Included(5));
+    /// map.append(&mut right);
+    ///
+    /// let mut want = SpanMap::new();
+    /// want.insert(0..10, "a");
+    /// assert_eq!(map, want);
+    /// ```
+    pub fn append(&mut self, other: &mut Self) {
+        let other = std::mem::take(other);
+
+        let Some(other_start) = other.content_start() else {
+            return;
+        };
+
+        if self.content_start().is_none() {
+            *self = other;
+            return;
+        }
+
+        let (self_end, self_end_set) = self.m.last_key_value().unwrap();
+
+        let concatenable = match other_start {
+            LeftBound::Unbounded => false,
+            _ => self_end_set.is_empty() && self_end <= other_start,
+        };
+
+        if concatenable {
+            let seam = other_start.clone();
+
+            let mut m = other.m;
+            m.remove(&LeftBound::Unbounded);
+            self.m.append(&mut m);
+
+            self.merge_adjacent_left(seam);
+        } else {
+            self.union_with(&other);
+        }
+    }
+
+    /// Returns the first boundary at which a non-empty value set starts, if any.
+    fn content_start(&self) -> Option<&LeftBound<K>> {
+        self.m
+            .iter()
+            .find(|(_b, set)| !set.is_empty())
+            .map(|(b, _set)| b)
+    }
+
+    /// Adds the values of `other` to `self` at every point.
+    fn union_with(&mut self, other: &Self) {
+        for b in other.m.keys() {
+            self.ensure_boundary(b.clone());
+        }
+
+        // At this point, every boundary of `other` is present in `self`
+
+        let mut other_it = other.m.iter().peekable();
+        let mut other_set = None;
+
+        for (b, set) in self.m.iter_mut() {
+            while let Some((_ob, os)) = other_it.next_if(|(ob, _os)| *ob <= b) {
+                other_set = Some(os);
+            }
+
+            if let Some(os) = other_set {
+                set.extend(os.iter().cloned());
+            }
+        }
+
+        self.merge_all_adjacent();
+    }
+
+    /// Moves every span by `delta`.
+    ///
+    /// For signed keys a negative `delta` moves the spans to the left.
+    ///
+    /// # Panics
+    ///
+    /// Panics if adding `delta` to a key panics, e.g., on integer overflow in debug builds, or if
+    /// the shifted keys are out of order, e.g., after a wrapping overflow. The map is left
+    /// unchanged.
+    ///
+    /// # Examples
+    /// ```
+    /// # use span_map::SpanMap;
+    ///
+    /// let mut map = SpanMap::new();
+    /// map.insert(0..10, "a");
+    ///
+    /// map.shift(5);
+    ///
+    /// assert_eq!(map.get(&4).count(), 0);
+    /// assert_eq!(map.get(&5).count(), 1);
+    /// assert_eq!(map.get(&14).count(), 1);
+    /// ```
+    pub fn shift(&mut self, delta: K)
+    where
+        K: Add<Output = K>,
+    {
+        // The new boundaries are computed before touching the map, so that a panic leaves it
+        // unchanged.
+        let bounds: Vec<LeftBound<K>> = self
+            .m
+            .keys()
+            .map(|b| b.clone().map(|k| k + delta.clone()))
+            .collect();
+
+        assert!(
+            bounds.windows(2).all(|w| w[0] < w[1]),
+            "shift(): the shifted keys are out of order"
+        );
+
+        let sets = std::mem::take(&mut self.m).into_values();
+        self.m = bounds.into_iter().zip(sets).collect();
+    }
+
+    /// Rewrites the key of every boundary with `f`, keeping the value sets unchanged.
+    ///
+    /// `f` must be strictly increasing over the keys in the map, so that the order of the
+    /// boundaries is preserved.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `f` does not preserve the order of the boundaries.
+    ///
+    /// # Examples
+    /// ```
+    /// # use span_map::SpanMap;
+    ///
+    /// let mut map = SpanMap::new();
+    /// map.insert(1..3, "a");
+    ///
+    /// let map = map.map_keys(|k| k * 10);
+    ///
+    /// assert_eq!(map.get(&9).count(), 0);
+    /// assert_eq!(map.get(&10).count(), 1);
+    /// assert_eq!(map.get(&29).count(), 1);
+    /// assert_eq!(map.get(&30).count(), 0);
+    /// ```
+    pub fn map_keys<K2, F>(self, mut f: F) -> SpanMap<K2, V>
+    where
+        K2: Clone + Ord,
+        F: FnMut(K) -> K2,
+    {
+        let mut entries: Vec<(LeftBound<K2>, BTreeSet<V>)> = Vec::with_capacity(self.m.len());
+
+        for (b, set) in self.m {
+            let b = b.map(&mut f);
+
+            if let Some((prev, _set)) = entries.last() {
+                assert!(
+                    *prev < b,
+                    "map_keys(): the key mapping function must be strictly increasing"
+                );
+            }
+
+            entries.push((b, set));
+        }
+
+        SpanMap {
+            m: entries.into_iter().collect(),
+        }
+    }
+
+    /// Converts every boundary into its canonical, inclusive form, for discrete keys.
+    ///
+    /// For discrete keys, `LeftBound::Excluded(5)` and `LeftBound::Included(6)` are the same
+    /// boundary. Mutations such as `insert(1..=5, v)` store the former, so spans that are really
+    /// adjacent would not be merged. This method rewrites every boundary with
+    /// [`LeftBound::canonical`], drops the empty segments between equivalent boundaries, and
+    /// merges neighbours with identical value sets.
+    ///
+    /// # Examples
+    /// ```
+    /// # use span_map::SpanMap;
+    ///
+    /// let mut map = SpanMap::new();
+    /// map.insert(1..=5, "a");
+    /// map.insert(6..=9, "a");
+    ///
+    /// map.canonicalize();
+    ///
+    /// let mut want = SpanMap::new();
+    /// want.insert(1..10, "a");
+    /// assert_eq!(map, want);
+    /// ```
+    pub fn canonicalize(&mut self)
+    where
+        K: Step,
+    {
+        // `canonical()` preserves the order of boundaries. When two boundaries become equal, the
+        // segment between them contains no key, and the later one wins.
+        let m = std::mem::take(&mut self.m);
+        self.m = m.into_iter().map(|(b, set)| (b.canonical(), set)).collect();
+
+        self.merge_all_adjacent();
+    }
+
+    /// Same as [`SpanMap::insert`], with the range given as a [`Span`].
     pub fn insert_span(&mut self, range: Span<K>, value: V) {
         self.update_set_in_span(range, |set| {
             set.insert(value.clone());
         });
     }
 
-    #[doc(hidden)]
+    /// Same as [`SpanMap::remove`], with the range given as a [`Span`].
     pub fn remove_span(&mut self, range: Span<K>, value: V) {
         self.update_set_in_span(range, |set| {
             set.remove(&value);
         });
     }
 
+    /// Applies `f` to every set in `span`, then merges at every updated boundary, so that no two
+    /// adjacent ranges have identical sets.
     fn update_set_in_span(&mut self, span: Span<K>, f: impl Fn(&mut BTreeSet<V>)) {
-        let start = span.left.clone();
-        self.ensure_boundary(start.clone());
+        // Removing a value may make the sets of two ranges inside the span identical, thus every
+        // updated boundary has to be checked, not only `start`.
+        for b in self.update_set_unmerged(span, f) {
+            self.merge_adjacent_left(b);
+        }
+    }
+
+    /// Applies `f` to every set in `span` without merging, and returns the boundaries that have
+    /// to be checked by [`SpanMap::merge_adjacent_left`] afterwards.
+    fn update_set_unmerged(
+        &mut self,
+        span: Span<K>,
+        f: impl Fn(&mut BTreeSet<V>),
+    ) -> Vec<LeftBound<K>> {
+        // An empty span must not introduce boundaries
+        if span.is_empty() {
+            return Vec::new();
+        }
+
+        self.ensure_boundary(span.left.clone());
 
         let end = span.right.adjacent_left();
         if let Some(end) = end.clone() {
@@ -145,17 +527,18 @@ where
 
         // At this point, `range.left` and `range.right` are ensured to be in the map
 
+        let mut updated = Vec::new();
+
         for (b, set) in self.m.range_mut(span.left..) {
             if span.right < *b {
                 break;
             }
             f(set);
+            updated.push(b.clone());
         }
 
-        self.merge_adjacent_left(start);
-        if let Some(end) = end {
-            self.merge_adjacent_left(end);
-        }
+        updated.extend(end);
+        updated
     }
 
     /// Splits a range at the specified boundary point and ensures the boundary exists in the map.
@@ -193,12 +576,28 @@ where
             self.m.remove(&right_bound);
         }
     }
+
+    /// Removes every boundary whose value set is identical to the one of the range to its left.
+    fn merge_all_adjacent(&mut self) {
+        let redundant: Vec<_> = self
+            .m
+            .iter()
+            .zip(self.m.iter().skip(1))
+            .filter(|((_lb, left_set), (_rb, right_set))| left_set == right_set)
+            .map(|(_left, (right_bound, _right_set))| right_bound.clone())
+            .collect();
+
+        for b in redundant {
+            self.m.remove(&b);
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::bounds::RightBound;
+    use crate::test_rng::TestRng;
 
     // ===================== get
 
@@ -361,6 +760,18 @@ mod tests {
 
     // ===================== insert
 
+    #[test]
+    fn test_insert_span_as_range() {
+        let mut map = SpanMap::<i32, &str>::new();
+
+        let span = Span::new(LeftBound::Excluded(1), RightBound::Included(5));
+        map.insert(span.clone(), "a");
+
+        let mut want = SpanMap::new();
+        want.insert_span(span, "a");
+        assert_eq!(map, want);
+    }
+
     #[test]
     fn test_insert_std_range() {
         let mut map = SpanMap::<i32, &str>::new();
@@ -748,6 +1159,611 @@ mod tests {
         );
     }
 
+    // ===================== merge
+
+    #[test]
+    fn test_remove_merges_inner_ranges() {
+        use LeftBound::*;
+        let mut map = SpanMap::<i32, i32>::new();
+
+        // [1,   5) -> {10, 20}
+        //      [5, 9) -> {10}
+        map.insert(1..9, 10);
+        map.insert(1..5, 20);
+
+        map.remove(0..10, 20);
+
+        assert_eq!(
+            map.m,
+            BTreeMap::from([
+                (Unbounded, BTreeSet::new()),
+                (Included(1), BTreeSet::from([10])),
+                (Included(9), BTreeSet::new()),
+            ])
+        );
+
+        map.remove(.., 10);
+        assert_eq!(map, SpanMap::new());
+    }
+
+    #[test]
+    fn test_insert_merges_inner_ranges() {
+        use LeftBound::*;
+        let mut map = SpanMap::<i32, i32>::new();
+
+        // [1,   5) -> {10}
+        //      [5, 9) -> {10, 20}
+        map.insert(1..9, 10);
+        map.insert(5..9, 20);
+
+        map.insert(0..10, 20);
+
+        assert_eq!(
+            map.m,
+            BTreeMap::from([
+                (Unbounded, BTreeSet::new()),
+                (Included(0), BTreeSet::from([20])),
+                (Included(1), BTreeSet::from([10, 20])),
+                (Included(9), BTreeSet::from([20])),
+                (Included(10), BTreeSet::new()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_adjacent_sets_differ_after_updates() {
+        let mut rng = TestRng::new(5);
+
+        for round in 0..200 {
+            let mut map = SpanMap::<i32, u8>::new();
+
+            for _ in 0..10 {
+                let (a, b) = (rng.below(20) as i32, rng.below(20) as i32);
+                let value = rng.below(3) as u8;
+                if rng.below(3) == 0 {
+                    map.remove(a..b, value);
+                } else {
+                    map.insert(a..b, value);
+                }
+
+                let sets: Vec<_> = map.m.values().collect();
+                assert!(
+                    sets.windows(2).all(|w| w[0] != w[1]),
+                    "round {}: {:?}",
+                    round,
+                    map.m
+                );
+            }
+        }
+    }
+
+    // ===================== empty spans
+
+    #[allow(clippy::reversed_empty_ranges)]
+    #[test]
+    fn test_insert_remove_empty_span() {
+        let mut map = SpanMap::<i32, i32>::new();
+        map.insert(1..10, 10);
+        let want = map.clone();
+
+        map.insert(5..5, 20);
+        map.insert(7..3, 20);
+        map.insert_span(
+            Span::new(LeftBound::Excluded(5), RightBound::Included(5)),
+            20,
+        );
+        assert_eq!(map, want);
+
+        map.remove(5..5, 10);
+        map.remove(7..3, 10);
+        assert_eq!(map, want);
+    }
+
+    #[allow(clippy::reversed_empty_ranges)]
+    #[test]
+    fn test_try_insert_remove() {
+        let mut map = SpanMap::<i32, i32>::new();
+
+        assert_eq!(map.try_insert(1..10, 10), Ok(()));
+        assert_eq!(map.try_insert(5..5, 20), Err(SpanError::Empty));
+        assert_eq!(map.try_insert(7..3, 20), Err(SpanError::Inverted));
+
+        assert_eq!(map.try_remove(5..5, 10), Err(SpanError::Empty));
+        assert_eq!(map.try_remove(7..=3, 10), Err(SpanError::Inverted));
+
+        let mut want = SpanMap::new();
+        want.insert(1..10, 10);
+        assert_eq!(map, want);
+
+        assert_eq!(map.try_remove(1..5, 10), Ok(()));
+
+        let mut want = SpanMap::new();
+        want.insert(5..10, 10);
+        assert_eq!(map, want);
+    }
+
+    // ===================== segments
+
+    #[test]
+    fn test_segments() {
+        let mut map = SpanMap::<i32, i32>::new();
+        map.insert(1..=5, 10);
+        map.insert(3.., 20);
+
+        let got: Vec<_> = map.iter().map(|(s, set)| (s, set.clone())).collect();
+
+        // The empty segment `(-∞, 1)` is skipped
+        assert_eq!(
+            got,
+            vec![
+                (Span::from_range(1..3), BTreeSet::from([10])),
+                (Span::from_range(3..=5), BTreeSet::from([10, 20])),
+                (
+                    Span::new(LeftBound::Excluded(5), RightBound::Unbounded),
+                    BTreeSet::from([20])
+                ),
+            ]
+        );
+
+        let map = SpanMap::<i32, i32>::new();
+        assert_eq!(map.iter().next(), None);
+    }
+
+    // ===================== split_off
+
+    #[test]
+    fn test_split_off_straddling_segment() {
+        use LeftBound::*;
+        let mut map = SpanMap::<i32, i32>::new();
+
+        // [1,       10] -> {10}
+        //      [5,       15] -> {20}
+        map.insert(1..=10, 10);
+        map.insert(5..=15, 20);
+
+        let right = map.split_off(&Included(7));
+
+        assert_eq!(
+            map.m,
+            BTreeMap::from([
+                (Unbounded, BTreeSet::new()),
+                (Included(1), BTreeSet::from([10])),
+                (Included(5), BTreeSet::from([10, 20])),
+                (Included(7), BTreeSet::new()),
+            ])
+        );
+        assert_eq!(
+            right.m,
+            BTreeMap::from([
+                (Unbounded, BTreeSet::new()),
+                (Included(7), BTreeSet::from([10, 20])),
+                (Excluded(10), BTreeSet::from([20])),
+                (Excluded(15), BTreeSet::new()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_split_off_at_existing_boundary() {
+        use LeftBound::*;
+        let mut map = SpanMap::<i32, i32>::new();
+
+        map.insert(1..5, 10);
+        map.insert(5..9, 20);
+
+        let right = map.split_off(&Included(5));
+
+        assert_eq!(
+            map.m,
+            BTreeMap::from([
+                (Unbounded, BTreeSet::new()),
+                (Included(1), BTreeSet::from([10])),
+                (Included(5), BTreeSet::new()),
+            ])
+        );
+        assert_eq!(
+            right.m,
+            BTreeMap::from([
+                (Unbounded, BTreeSet::new()),
+                (Included(5), BTreeSet::from([20])),
+                (Included(9), BTreeSet::new()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_split_off_in_empty_region() {
+        let mut map = SpanMap::<i32, i32>::new();
+
+        map.insert(1..5, 10);
+        map.insert(10..15, 20);
+
+        let right = map.split_off(&LeftBound::Included(7));
+
+        let mut want_left = SpanMap::new();
+        want_left.insert(1..5, 10);
+        assert_eq!(map, want_left);
+
+        let mut want_right = SpanMap::new();
+        want_right.insert(10..15, 20);
+        assert_eq!(right, want_right);
+    }
+
+    #[test]
+    fn test_split_off_unbounded() {
+        let mut map = SpanMap::<i32, i32>::new();
+        map.insert(..5, 10);
+
+        let want = map.clone();
+        let right = map.split_off(&LeftBound::Unbounded);
+
+        assert_eq!(map, SpanMap::new());
+        assert_eq!(right, want);
+    }
+
+    #[test]
+    fn test_split_off_unbounded_right() {
+        let mut map = SpanMap::<i32, i32>::new();
+        map.insert(1.., 10);
+
+        let right = map.split_off(&LeftBound::Excluded(3));
+
+        let mut want_left = SpanMap::new();
+        want_left.insert(1..=3, 10);
+        assert_eq!(map, want_left);
+
+        let mut want_right = SpanMap::new();
+        want_right.insert_span(Span::new(LeftBound::Excluded(3), RightBound::Unbounded), 10);
+        assert_eq!(right, want_right);
+    }
+
+    // ===================== truncate
+
+    #[test]
+    fn test_truncate() {
+        use LeftBound::*;
+        let mut map = SpanMap::<i32, i32>::new();
+
+        map.insert(1..10, 10);
+        map.insert(12..15, 20);
+
+        map.truncate(&Excluded(5));
+        assert_eq!(
+            map.m,
+            BTreeMap::from([
+                (Unbounded, BTreeSet::new()),
+                (Included(1), BTreeSet::from([10])),
+                (Excluded(5), BTreeSet::new()),
+            ])
+        );
+
+        map.truncate(&Included(1));
+        assert_eq!(map, SpanMap::new());
+
+        map.insert(..3, 10);
+        map.truncate(&Unbounded);
+        assert_eq!(map, SpanMap::new());
+    }
+
+    // ===================== append
+
+    #[test]
+    fn test_append_disjoint() {
+        use LeftBound::*;
+        let mut map = SpanMap::<i32, i32>::new();
+        map.insert(1..5, 10);
+
+        let mut other = SpanMap::new();
+        other.insert(7..9, 20);
+
+        map.append(&mut other);
+
+        assert_eq!(other, SpanMap::new());
+        assert_eq!(
+            map.m,
+            BTreeMap::from([
+                (Unbounded, BTreeSet::new()),
+                (Included(1), BTreeSet::from([10])),
+                (Included(5), BTreeSet::new()),
+                (Included(7), BTreeSet::from([20])),
+                (Included(9), BTreeSet::new()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_append_adjacent_merges_seam() {
+        use LeftBound::*;
+        let mut map = SpanMap::<i32, i32>::new();
+        map.insert(1..5, 10);
+
+        let mut other = SpanMap::new();
+        other.insert(5..9, 10);
+        other.insert(7..9, 20);
+
+        map.append(&mut other);
+
+        assert_eq!(
+            map.m,
+            BTreeMap::from([
+                (Unbounded, BTreeSet::new()),
+                (Included(1), BTreeSet::from([10])),
+                (Included(7), BTreeSet::from([10, 20])),
+                (Included(9), BTreeSet::new()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_append_split_off_roundtrip() {
+        let mut map = SpanMap::<i32, i32>::new();
+        map.insert(1..=10, 10);
+        map.insert(5..=15, 20);
+        map.insert(20.., 30);
+
+        let want = map.clone();
+
+        for bound in [
+            LeftBound::Unbounded,
+            LeftBound::Included(5),
+            LeftBound::Excluded(5),
+            LeftBound::Included(17),
+            LeftBound::Included(25),
+        ] {
+            let mut m = want.clone();
+            let mut right = m.split_off(&bound);
+            m.append(&mut right);
+            assert_eq!(m, want, "split at {}", bound);
+        }
+    }
+
+    #[test]
+    fn test_append_overlapping_falls_back_to_union() {
+        let mut map = SpanMap::<i32, i32>::new();
+        map.insert(1..10, 10);
+
+        let mut other = SpanMap::new();
+        other.insert(5..15, 10);
+        other.insert(5..15, 20);
+
+        map.append(&mut other);
+
+        let mut want = SpanMap::new();
+        want.insert(1..15, 10);
+        want.insert(5..15, 20);
+        assert_eq!(map, want);
+    }
+
+    #[test]
+    fn test_append_unbounded() {
+        // `self` extends to infinity
+        let mut map = SpanMap::<i32, i32>::new();
+        map.insert(1.., 10);
+
+        let mut other = SpanMap::new();
+        other.insert(5..7, 20);
+
+        map.append(&mut other);
+
+        let mut want = SpanMap::new();
+        want.insert(1.., 10);
+        want.insert(5..7, 20);
+        assert_eq!(map, want);
+
+        // `other` extends to negative infinity
+        let mut map = SpanMap::<i32, i32>::new();
+        map.insert(5..7, 10);
+
+        let mut other = SpanMap::new();
+        other.insert(..1, 20);
+
+        map.append(&mut other);
+
+        let mut want = SpanMap::new();
+        want.insert(5..7, 10);
+        want.insert(..1, 20);
+        assert_eq!(map, want);
+    }
+
+    #[test]
+    fn test_append_empty() {
+        let mut map = SpanMap::<i32, i32>::new();
+        map.insert(1..5, 10);
+        let want = map.clone();
+
+        map.append(&mut SpanMap::new());
+        assert_eq!(map, want);
+
+        let mut empty = SpanMap::new();
+        empty.append(&mut map);
+        assert_eq!(empty, want);
+        assert_eq!(map, SpanMap::new());
+    }
+
+    // ===================== shift
+
+    #[test]
+    fn test_shift() {
+        let mut map = SpanMap::<i32, i32>::new();
+        map.insert(1..=5, 10);
+        map.insert(3..7, 20);
+
+        map.shift(10);
+
+        let mut want = SpanMap::new();
+        want.insert(11..=15, 10);
+        want.insert(13..17, 20);
+        assert_eq!(map, want);
+
+        map.shift(-20);
+
+        let mut want = SpanMap::new();
+        want.insert(-9..=-5, 10);
+        want.insert(-7..-3, 20);
+        assert_eq!(map, want);
+    }
+
+    #[test]
+    fn test_shift_unbounded() {
+        let mut map = SpanMap::<u64, i32>::new();
+        map.insert(..5, 10);
+        map.insert(8.., 20);
+
+        map.shift(100);
+
+        let mut want = SpanMap::new();
+        want.insert(..105, 10);
+        want.insert(108.., 20);
+        assert_eq!(map, want);
+    }
+
+    #[test]
+    fn test_shift_overflow_leaves_map_unchanged() {
+        let mut map = SpanMap::<u8, i32>::new();
+        map.insert(240..250, 10);
+        let before = map.clone();
+
+        // 250 + 10 overflows: it panics in debug builds, and wraps to 4 in release builds
+
+        let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| map.shift(10)));
+        assert!(res.is_err());
+        assert_eq!(map, before);
+    }
+
+    // ===================== map_keys
+
+    #[test]
+    fn test_map_keys() {
+        use LeftBound::*;
+
+        let mut map = SpanMap::<i32, i32>::new();
+        map.insert(1..=5, 10);
+        map.insert(5..7, 20);
+
+        let map = map.map_keys(|k| format!("k{:02}", k));
+
+        assert_eq!(
+            map.m,
+            BTreeMap::from([
+                (Unbounded, BTreeSet::new()),
+                (Included("k01".to_string()), BTreeSet::from([10])),
+                (Included("k05".to_string()), BTreeSet::from([10, 20])),
+                (Excluded("k05".to_string()), BTreeSet::from([20])),
+                (Included("k07".to_string()), BTreeSet::new()),
+            ])
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "strictly increasing")]
+    fn test_map_keys_not_monotone() {
+        let mut map = SpanMap::<i32, i32>::new();
+        map.insert(1..5, 10);
+
+        let _ = map.map_keys(|k| -k);
+    }
+
+    #[test]
+    #[should_panic(expected = "strictly increasing")]
+    fn test_map_keys_collapsing() {
+        let mut map = SpanMap::<i32, i32>::new();
+        map.insert(1..5, 10);
+
+        let _ = map.map_keys(|k| k / 10);
+    }
+
+    // ===================== canonicalize
+
+    #[test]
+    fn test_canonicalize() {
+        use LeftBound::*;
+
+        let mut map = SpanMap::<i32, i32>::new();
+        map.insert(1..=5, 10);
+        map.insert(6..=9, 10);
+        map.insert(6..=7, 20);
+
+        assert_eq!(map.m.len(), 6);
+
+        map.canonicalize();
+
+        assert_eq!(
+            map.m,
+            BTreeMap::from([
+                (Unbounded, BTreeSet::new()),
+                (Included(1), BTreeSet::from([10])),
+                (Included(6), BTreeSet::from([10, 20])),
+                (Included(8), BTreeSet::from([10])),
+                (Included(10), BTreeSet::new()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_canonicalize_drops_slivers() {
+        use LeftBound::*;
+
+        // (5, 6) contains no integer
+        let mut map = SpanMap::<i32, i32>::new();
+        map.insert_span(Span::new(Excluded(5), RightBound::Excluded(6)), 10);
+        map.insert(6..8, 20);
+
+        map.canonicalize();
+
+        assert_eq!(
+            map.m,
+            BTreeMap::from([
+                (Unbounded, BTreeSet::new()),
+                (Included(6), BTreeSet::from([20])),
+                (Included(8), BTreeSet::new()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_canonicalize_max_key() {
+        use LeftBound::*;
+
+        let mut map = SpanMap::<u8, i32>::new();
+        map.insert(250..=u8::MAX, 10);
+
+        map.canonicalize();
+
+        assert_eq!(
+            map.m,
+            BTreeMap::from([
+                (Unbounded, BTreeSet::new()),
+                (Included(250), BTreeSet::from([10])),
+                (Excluded(u8::MAX), BTreeSet::new()),
+            ])
+        );
+    }
+
+    // ===================== merge_all_adjacent
+
+    #[test]
+    fn test_merge_all_adjacent() {
+        use LeftBound::*;
+
+        let mut map = SpanMap::<i32, i32>::new();
+        map.m.insert(Included(1), BTreeSet::from([1]));
+        map.m.insert(Included(2), BTreeSet::from([1]));
+        map.m.insert(Included(3), BTreeSet::from([1]));
+        map.m.insert(Included(4), BTreeSet::new());
+        map.m.insert(Included(5), BTreeSet::new());
+
+        map.merge_all_adjacent();
+
+        assert_eq!(
+            map.m,
+            BTreeMap::from([
+                (Unbounded, BTreeSet::new()),
+                (Included(1), BTreeSet::from([1])),
+                (Included(4), BTreeSet::new()),
+            ])
+        );
+    }
+
     // ===================== ensure_boundary
 
     #[test]
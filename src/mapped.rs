@@ -0,0 +1,512 @@
+//! A read-only map of `u64` keys to `u32` values, served directly from a byte slice.
+//!
+//! The file layout, all integers little-endian:
+//!
+//! ```text
+//! header   magic b"SPMM", u32 version = 1, u64 record count, u64 value count
+//! records  one 24-byte record per boundary, in key order:
+//!              u64 key, u8 kind (0 = Unbounded, 1 = Included, 2 = Excluded), 3 bytes padding,
+//!              u32 value count, u64 index of the first value in the value pool
+//! values   the value pool, one u32 per value
+//! ```
+//!
+//! Every record is a [`LeftBound`] of the map and the sorted value set of the segment starting
+//! there, so a lookup is a binary search over fixed-size records.
+
+use std::io;
+use std::io::Write;
+use std::iter::FusedIterator;
+use std::ops::RangeBounds;
+
+use crate::bounds::LeftBound;
+use crate::bounds::RightBound;
+use crate::Span;
+use crate::SpanMap;
+
+const MAGIC: &[u8; 4] = b"SPMM";
+const VERSION: u32 = 1;
+
+const HEADER_SIZE: usize = 24;
+const RECORD_SIZE: usize = 24;
+const VALUE_SIZE: usize = 4;
+
+const KIND_UNBOUNDED: u8 = 0;
+const KIND_INCLUDED: u8 = 1;
+const KIND_EXCLUDED: u8 = 2;
+
+/// A read-only `SpanMap<u64, u32>` that answers queries directly from its encoded bytes.
+///
+/// The bytes are written by [`SpanMap::write_mapped`], and are typically a memory-mapped file,
+/// so that opening a map of any size costs nothing more than checking its header.
+///
+/// [`MappedSpanMap::new`] only checks the header and the size of the data. Use
+/// [`MappedSpanMap::validate`] to check every record of data that may be corrupt: queries on
+/// corrupt records return wrong results, but never panic.
+///
+/// # Examples
+/// ```
+/// # use span_map::MappedSpanMap;
+/// # use span_map::SpanMap;
+///
+/// let mut map = SpanMap::<u64, u32>::new();
+/// map.insert(10..20, 1);
+/// map.insert(15..30, 2);
+///
+/// let mut buf = Vec::new();
+/// map.write_mapped(&mut buf).unwrap();
+///
+/// let mapped = MappedSpanMap::new(&buf).unwrap();
+/// assert_eq!(mapped.get(17).collect::<Vec<_>>(), vec![1, 2]);
+/// assert_eq!(mapped.get(25).collect::<Vec<_>>(), vec![2]);
+/// assert_eq!(mapped.to_span_map(), map);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct MappedSpanMap<'a> {
+    records: &'a [u8],
+    values: &'a [u8],
+}
+
+/// The values of a segment of a [`MappedSpanMap`], in increasing order.
+#[derive(Debug, Clone)]
+pub struct MappedValues<'a> {
+    values: std::slice::ChunksExact<'a, u8>,
+}
+
+impl Iterator for MappedValues<'_> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        self.values.next().map(read_u32)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.values.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for MappedValues<'_> {
+    fn next_back(&mut self) -> Option<u32> {
+        self.values.next_back().map(read_u32)
+    }
+}
+
+impl ExactSizeIterator for MappedValues<'_> {}
+
+impl FusedIterator for MappedValues<'_> {}
+
+impl<'a> MappedSpanMap<'a> {
+    /// Opens a map written by [`SpanMap::write_mapped`].
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidData`] if the header is invalid, or if
+    /// `data` is not exactly as long as the header says.
+    pub fn new(data: &'a [u8]) -> io::Result<Self> {
+        if data.len() < HEADER_SIZE || &data[..4] != MAGIC {
+            return Err(invalid_data("not a mapped SpanMap: bad magic"));
+        }
+
+        let version = read_u32(&data[4..8]);
+        if version != VERSION {
+            return Err(invalid_data(format!(
+                "unsupported mapped SpanMap version: {}",
+                version
+            )));
+        }
+
+        let record_count = read_u64(&data[8..16]);
+        let value_count = read_u64(&data[16..24]);
+
+        let records_size = usize::try_from(record_count)
+            .ok()
+            .and_then(|n| n.checked_mul(RECORD_SIZE));
+        let values_size = usize::try_from(value_count)
+            .ok()
+            .and_then(|n| n.checked_mul(VALUE_SIZE));
+        let total = records_size
+            .zip(values_size)
+            .and_then(|(r, v)| r.checked_add(v)?.checked_add(HEADER_SIZE));
+
+        let (Some(records_size), Some(total)) = (records_size, total) else {
+            return Err(invalid_data("mapped SpanMap sizes overflow"));
+        };
+
+        if total != data.len() {
+            return Err(invalid_data(format!(
+                "mapped SpanMap size mismatch: expected {} bytes, got {}",
+                total,
+                data.len()
+            )));
+        }
+
+        if record_count == 0 {
+            return Err(invalid_data("mapped SpanMap has no record"));
+        }
+
+        let (records, values) = data[HEADER_SIZE..].split_at(records_size);
+        Ok(Self { records, values })
+    }
+
+    /// Checks every record: the boundaries must start with `Unbounded` and be in increasing
+    /// order, and every value set must lie within the value pool and be in increasing order.
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidData`] describing the first invalid
+    /// record.
+    pub fn validate(&self) -> io::Result<()> {
+        let mut prev: Option<LeftBound<u64>> = None;
+
+        for i in 0..self.record_count() {
+            let invalid = |msg: &str| invalid_data(format!("record {}: {}", i, msg));
+
+            let record = self.record(i);
+            let bound = match record[8] {
+                KIND_UNBOUNDED => LeftBound::Unbounded,
+                KIND_INCLUDED => LeftBound::Included(read_u64(record)),
+                KIND_EXCLUDED => LeftBound::Excluded(read_u64(record)),
+                _ => return Err(invalid("invalid bound kind")),
+            };
+
+            match &prev {
+                None if bound != LeftBound::Unbounded => {
+                    return Err(invalid("the first boundary must be unbounded"))
+                }
+                Some(p) if p >= &bound => return Err(invalid("boundaries are not increasing")),
+                _ => {}
+            }
+            prev = Some(bound);
+
+            let count = read_u32(&record[12..16]) as u64;
+            let start = read_u64(&record[16..24]);
+            let end = start.checked_add(count);
+            if end.is_none_or(|end| end > (self.values.len() / VALUE_SIZE) as u64) {
+                return Err(invalid("value set out of the value pool"));
+            }
+
+            let values: Vec<u32> = self.values_at(i).collect();
+            if values.windows(2).any(|w| w[0] >= w[1]) {
+                return Err(invalid("values are not increasing"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the values at `key`, in increasing order.
+    pub fn get(&self, key: u64) -> MappedValues<'a> {
+        self.values_at(self.segment_index(&LeftBound::Included(key)))
+    }
+
+    /// Returns the non-empty segments that overlap `range`, in key order.
+    ///
+    /// Segments are returned whole, not clipped to `range`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use span_map::MappedSpanMap;
+    /// # use span_map::Span;
+    /// # use span_map::SpanMap;
+    ///
+    /// let mut map = SpanMap::<u64, u32>::new();
+    /// map.insert(10..20, 1);
+    /// map.insert(15..30, 2);
+    /// map.insert(40..50, 3);
+    ///
+    /// let mut buf = Vec::new();
+    /// map.write_mapped(&mut buf).unwrap();
+    /// let mapped = MappedSpanMap::new(&buf).unwrap();
+    ///
+    /// let segments: Vec<_> = mapped
+    ///     .range(18..42)
+    ///     .map(|(span, values)| (span, values.collect::<Vec<_>>()))
+    ///     .collect();
+    ///
+    /// assert_eq!(
+    ///     segments,
+    ///     vec![
+    ///         (Span::from_range(15..20), vec![1, 2]),
+    ///         (Span::from_range(20..30), vec![2]),
+    ///         (Span::from_range(40..50), vec![3]),
+    ///     ]
+    /// );
+    /// ```
+    pub fn range<R>(&self, range: R) -> impl Iterator<Item = (Span<u64>, MappedValues<'a>)> + 'a
+    where
+        R: RangeBounds<u64>,
+    {
+        let query = Span::from_range(range);
+        let this = *self;
+
+        let first = if query.is_empty() {
+            this.record_count()
+        } else {
+            this.segment_index(query.left())
+        };
+
+        (first..this.record_count())
+            .map(move |i| (this.span_at(i), this.values_at(i)))
+            .take_while(move |(span, _values)| span.left() <= query.right())
+            .filter(|(_span, values)| values.len() > 0)
+    }
+
+    /// Returns all non-empty segments, in key order.
+    pub fn iter(&self) -> impl Iterator<Item = (Span<u64>, MappedValues<'a>)> + 'a {
+        self.range(..)
+    }
+
+    /// Builds an in-memory [`SpanMap`] with the same content.
+    pub fn to_span_map(&self) -> SpanMap<u64, u32> {
+        let mut map = SpanMap::new();
+        for (span, values) in self.iter() {
+            for v in values {
+                map.insert_span(span.clone(), v);
+            }
+        }
+        map
+    }
+
+    fn record_count(&self) -> usize {
+        self.records.len() / RECORD_SIZE
+    }
+
+    fn record(&self, i: usize) -> &'a [u8] {
+        &self.records[i * RECORD_SIZE..(i + 1) * RECORD_SIZE]
+    }
+
+    fn bound_at(&self, i: usize) -> LeftBound<u64> {
+        let record = self.record(i);
+        match record[8] {
+            KIND_INCLUDED => LeftBound::Included(read_u64(record)),
+            KIND_EXCLUDED => LeftBound::Excluded(read_u64(record)),
+            _ => LeftBound::Unbounded,
+        }
+    }
+
+    fn span_at(&self, i: usize) -> Span<u64> {
+        let right = if i + 1 < self.record_count() {
+            self.bound_at(i + 1)
+                .adjacent_right()
+                .unwrap_or(RightBound::Unbounded)
+        } else {
+            RightBound::Unbounded
+        };
+
+        Span::new(self.bound_at(i), right)
+    }
+
+    /// Returns the index of the segment that contains `bound`, i.e., the last boundary that is
+    /// less than or equal to it.
+    fn segment_index(&self, bound: &LeftBound<u64>) -> usize {
+        // Record 0 is `Unbounded`, which is less than or equal to any bound.
+        let (mut lo, mut hi) = (0, self.record_count());
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            if &self.bound_at(mid) <= bound {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        lo
+    }
+
+    fn values_at(&self, i: usize) -> MappedValues<'a> {
+        let record = self.record(i);
+        let count = read_u32(&record[12..16]) as usize;
+        let start = read_u64(&record[16..24]) as usize;
+
+        let values = start
+            .checked_mul(VALUE_SIZE)
+            .zip(count.checked_mul(VALUE_SIZE))
+            .and_then(|(s, c)| self.values.get(s..s.checked_add(c)?))
+            .unwrap_or_default();
+
+        MappedValues {
+            values: values.chunks_exact(VALUE_SIZE),
+        }
+    }
+}
+
+impl SpanMap<u64, u32> {
+    /// Writes the map in the format read by [`MappedSpanMap`].
+    pub fn write_mapped<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let value_count: usize = self.m.values().map(|set| set.len()).sum();
+
+        w.write_all(MAGIC)?;
+        w.write_all(&VERSION.to_le_bytes())?;
+        w.write_all(&(self.m.len() as u64).to_le_bytes())?;
+        w.write_all(&(value_count as u64).to_le_bytes())?;
+
+        let mut offset = 0u64;
+        for (bound, set) in &self.m {
+            let (key, kind) = match bound {
+                LeftBound::Unbounded => (0, KIND_UNBOUNDED),
+                LeftBound::Included(k) => (*k, KIND_INCLUDED),
+                LeftBound::Excluded(k) => (*k, KIND_EXCLUDED),
+            };
+            let count = u32::try_from(set.len()).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "too many values in a segment for a mapped SpanMap",
+                )
+            })?;
+
+            w.write_all(&key.to_le_bytes())?;
+            w.write_all(&[kind, 0, 0, 0])?;
+            w.write_all(&count.to_le_bytes())?;
+            w.write_all(&offset.to_le_bytes())?;
+
+            offset += count as u64;
+        }
+
+        for set in self.m.values() {
+            for v in set {
+                w.write_all(&v.to_le_bytes())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn invalid_data(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+fn read_u32(b: &[u8]) -> u32 {
+    // Safe unwrap(): callers pass at least 4 bytes
+    u32::from_le_bytes(b[..4].try_into().unwrap())
+}
+
+fn read_u64(b: &[u8]) -> u64 {
+    // Safe unwrap(): callers pass at least 8 bytes
+    u64::from_le_bytes(b[..8].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+
+    use super::*;
+
+    fn mapped_bytes(map: &SpanMap<u64, u32>) -> Vec<u8> {
+        let mut buf = Vec::new();
+        map.write_mapped(&mut buf).unwrap();
+        buf
+    }
+
+    fn sample() -> SpanMap<u64, u32> {
+        let mut map = SpanMap::new();
+        map.insert(..=5, 7);
+        map.insert(3..10, 1);
+        map.insert_span(
+            Span::new(LeftBound::Excluded(10), RightBound::Included(20)),
+            2,
+        );
+        map.insert(15.., 3);
+        map
+    }
+
+    #[test]
+    fn test_layout() {
+        let mut map = SpanMap::new();
+        map.insert(1..2, 9);
+
+        let buf = mapped_bytes(&map);
+        assert_eq!(buf.len(), HEADER_SIZE + 3 * RECORD_SIZE + VALUE_SIZE);
+        assert_eq!(&buf[..4], b"SPMM");
+        assert_eq!(read_u64(&buf[8..]), 3);
+        assert_eq!(read_u64(&buf[16..]), 1);
+        assert_eq!(read_u32(&buf[buf.len() - 4..]), 9);
+    }
+
+    #[test]
+    fn test_get() {
+        let map = sample();
+        let buf = mapped_bytes(&map);
+        let mapped = MappedSpanMap::new(&buf).unwrap();
+        mapped.validate().unwrap();
+
+        for key in [0, 3, 5, 6, 9, 10, 11, 15, 20, 21, u64::MAX] {
+            let want: Vec<u32> = map.get(&key).copied().collect();
+            assert_eq!(mapped.get(key).collect::<Vec<_>>(), want, "key {}", key);
+        }
+
+        assert_eq!(mapped.get(16).len(), 2);
+        assert_eq!(mapped.get(16).rev().collect::<Vec<_>>(), vec![3, 2]);
+    }
+
+    #[test]
+    fn test_range() {
+        let map = sample();
+        let buf = mapped_bytes(&map);
+        let mapped = MappedSpanMap::new(&buf).unwrap();
+
+        let spans = |r: (Bound<u64>, Bound<u64>)| -> Vec<Span<u64>> {
+            mapped.range(r).map(|(span, _values)| span).collect()
+        };
+
+        assert_eq!(
+            spans((Bound::Excluded(10), Bound::Excluded(15))),
+            vec![Span::new(LeftBound::Excluded(10), RightBound::Excluded(15))]
+        );
+        assert_eq!(
+            spans((Bound::Included(9), Bound::Included(10))),
+            vec![Span::new(LeftBound::Excluded(5), RightBound::Excluded(10))]
+        );
+        // The segment `[10, 10]` has no value
+        assert!(spans((Bound::Included(10), Bound::Included(10))).is_empty());
+        assert!(spans((Bound::Excluded(10), Bound::Excluded(10))).is_empty());
+        assert_eq!(mapped.iter().count(), map.iter().count());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let empty = SpanMap::new();
+        let buf = mapped_bytes(&empty);
+        let mapped = MappedSpanMap::new(&buf).unwrap();
+        mapped.validate().unwrap();
+        assert_eq!(mapped.get(1).count(), 0);
+        assert_eq!(mapped.to_span_map(), empty);
+
+        let map = sample();
+        assert_eq!(
+            MappedSpanMap::new(&mapped_bytes(&map))
+                .unwrap()
+                .to_span_map(),
+            map
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        let buf = mapped_bytes(&sample());
+
+        let err = MappedSpanMap::new(&buf[..buf.len() - 1]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("size mismatch"), "{}", err);
+
+        let mut bad = buf.clone();
+        bad[0] = b'X';
+        assert!(MappedSpanMap::new(&bad).is_err());
+
+        let mut bad = buf.clone();
+        bad[4] = 9;
+        assert!(MappedSpanMap::new(&bad).is_err());
+
+        // Decreasing keys: only detected by validate()
+        let mut bad = buf.clone();
+        let second_record = HEADER_SIZE + RECORD_SIZE;
+        bad[second_record..second_record + 8].copy_from_slice(&100u64.to_le_bytes());
+        let mapped = MappedSpanMap::new(&bad).unwrap();
+        let err = mapped.validate().unwrap_err();
+        assert_eq!(err.to_string(), "record 2: boundaries are not increasing");
+
+        // Value offset out of the pool: queries return nothing instead of panicking
+        let mut bad = buf.clone();
+        bad[second_record + 16..second_record + 24].copy_from_slice(&u64::MAX.to_le_bytes());
+        let mapped = MappedSpanMap::new(&bad).unwrap();
+        assert_eq!(
+            mapped.validate().unwrap_err().to_string(),
+            "record 1: value set out of the value pool"
+        );
+        assert_eq!(mapped.get(4).count(), 0);
+    }
+}
@@ -0,0 +1,422 @@
+//! Parsing of spans and bounds from interval notation, e.g., `[1, 5)` or `(-∞, 3]`.
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::bounds::LeftBound;
+use crate::bounds::RightBound;
+use crate::Span;
+
+/// The error returned when parsing a [`Span`], [`LeftBound`] or [`RightBound`] from a string
+/// fails.
+///
+/// `E` is the error type of parsing a key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseSpanError<E> {
+    position: usize,
+    kind: ParseSpanErrorKind<E>,
+}
+
+/// The reason a [`ParseSpanError`] occurred.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseSpanErrorKind<E> {
+    /// A left bound does not start with `[` or `(`.
+    MissingOpeningBracket,
+
+    /// A right bound does not end with `]` or `)`.
+    MissingClosingBracket,
+
+    /// There is no `,` between the left and the right bound.
+    MissingSeparator,
+
+    /// An infinite bound is written with `[` or `]`, e.g., `[-∞, 5)`.
+    InclusiveInfinity,
+
+    /// A key could not be parsed.
+    Key(E),
+}
+
+impl<E> ParseSpanError<E> {
+    fn new(position: usize, kind: ParseSpanErrorKind<E>) -> Self {
+        Self { position, kind }
+    }
+
+    /// Returns the byte offset in the input at which the error was detected.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the reason of the error.
+    pub fn kind(&self) -> &ParseSpanErrorKind<E> {
+        &self.kind
+    }
+}
+
+impl<E> fmt::Display for ParseSpanErrorKind<E>
+where
+    E: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseSpanErrorKind::MissingOpeningBracket => write!(f, "expected '[' or '('"),
+            ParseSpanErrorKind::MissingClosingBracket => write!(f, "expected ']' or ')'"),
+            ParseSpanErrorKind::MissingSeparator => write!(f, "expected ','"),
+            ParseSpanErrorKind::InclusiveInfinity => {
+                write!(f, "an infinite bound must be exclusive")
+            }
+            ParseSpanErrorKind::Key(e) => write!(f, "invalid key: {}", e),
+        }
+    }
+}
+
+impl<E> fmt::Display for ParseSpanError<E>
+where
+    E: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.kind, self.position)
+    }
+}
+
+impl<E> Error for ParseSpanError<E>
+where
+    E: Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            ParseSpanErrorKind::Key(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Parses a left bound such as `[1`, `(1` or `(-∞`.
+///
+/// `-inf` is accepted in place of `-∞`. Whitespace around the bracket and the key is ignored.
+impl<K> FromStr for LeftBound<K>
+where
+    K: FromStr,
+{
+    type Err = ParseSpanError<K::Err>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_left(s, 0)
+    }
+}
+
+/// Parses a right bound such as `5]`, `5)` or `∞)`.
+///
+/// `inf`, `+inf` and `+∞` are accepted in place of `∞`. Whitespace around the bracket and the key
+/// is ignored.
+impl<K> FromStr for RightBound<K>
+where
+    K: FromStr,
+{
+    type Err = ParseSpanError<K::Err>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_right(s, 0)
+    }
+}
+
+/// Parses a span in interval notation, as produced by its `Display` implementation.
+///
+/// The left and the right bound are separated by the first `,` in the input, thus keys
+/// containing a `,` are not supported.
+///
+/// # Examples
+/// ```
+/// # use span_map::Span;
+///
+/// let span: Span<i32> = "[1, 5)".parse().unwrap();
+/// assert_eq!(span, Span::from_range(1..5));
+///
+/// let span: Span<i32> = "(-inf, 3]".parse().unwrap();
+/// assert_eq!(span, Span::from_range(..=3));
+///
+/// let err = "[1, x)".parse::<Span<i32>>().unwrap_err();
+/// assert_eq!(err.position(), 4);
+/// ```
+impl<K> FromStr for Span<K>
+where
+    K: FromStr + Ord,
+{
+    type Err = ParseSpanError<K::Err>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some(comma) = s.find(',') else {
+            return Err(ParseSpanError::new(
+                s.len(),
+                ParseSpanErrorKind::MissingSeparator,
+            ));
+        };
+
+        let left = parse_left(&s[..comma], 0)?;
+        let right = parse_right(&s[comma + 1..], comma + 1)?;
+
+        Ok(Span::new(left, right))
+    }
+}
+
+/// Parses a left bound from `s`, which starts at byte `offset` of the whole input.
+fn parse_left<K>(s: &str, offset: usize) -> Result<LeftBound<K>, ParseSpanError<K::Err>>
+where
+    K: FromStr,
+{
+    let bracket_pos = leading_whitespace(s);
+    let rest = &s[bracket_pos..];
+
+    let included = match rest.chars().next() {
+        Some('[') => true,
+        Some('(') => false,
+        _ => {
+            return Err(ParseSpanError::new(
+                offset + bracket_pos,
+                ParseSpanErrorKind::MissingOpeningBracket,
+            ))
+        }
+    };
+
+    let key_str = &rest[1..];
+    let key_pos = offset + bracket_pos + 1 + leading_whitespace(key_str);
+    let key_str = key_str.trim();
+
+    if matches!(key_str, "-∞" | "-inf") {
+        return if included {
+            Err(ParseSpanError::new(
+                offset + bracket_pos,
+                ParseSpanErrorKind::InclusiveInfinity,
+            ))
+        } else {
+            Ok(LeftBound::Unbounded)
+        };
+    }
+
+    let key = parse_key(key_str, key_pos)?;
+
+    if included {
+        Ok(LeftBound::Included(key))
+    } else {
+        Ok(LeftBound::Excluded(key))
+    }
+}
+
+/// Parses a right bound from `s`, which starts at byte `offset` of the whole input.
+fn parse_right<K>(s: &str, offset: usize) -> Result<RightBound<K>, ParseSpanError<K::Err>>
+where
+    K: FromStr,
+{
+    let rest = s.trim_end();
+
+    let included = match rest.chars().last() {
+        Some(']') => true,
+        Some(')') => false,
+        _ => {
+            return Err(ParseSpanError::new(
+                offset + rest.len(),
+                ParseSpanErrorKind::MissingClosingBracket,
+            ))
+        }
+    };
+
+    let bracket_pos = offset + rest.len() - 1;
+
+    let key_str = &rest[..rest.len() - 1];
+    let key_pos = offset + leading_whitespace(key_str);
+    let key_str = key_str.trim();
+
+    if matches!(key_str, "∞" | "+∞" | "inf" | "+inf") {
+        return if included {
+            Err(ParseSpanError::new(
+                bracket_pos,
+                ParseSpanErrorKind::InclusiveInfinity,
+            ))
+        } else {
+            Ok(RightBound::Unbounded)
+        };
+    }
+
+    let key = parse_key(key_str, key_pos)?;
+
+    if included {
+        Ok(RightBound::Included(key))
+    } else {
+        Ok(RightBound::Excluded(key))
+    }
+}
+
+fn parse_key<K>(s: &str, position: usize) -> Result<K, ParseSpanError<K::Err>>
+where
+    K: FromStr,
+{
+    s.parse()
+        .map_err(|e| ParseSpanError::new(position, ParseSpanErrorKind::Key(e)))
+}
+
+/// Returns the length in bytes of the whitespace at the start of `s`.
+fn leading_whitespace(s: &str) -> usize {
+    s.len() - s.trim_start().len()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::ParseIntError;
+
+    use super::*;
+
+    fn key_err(s: &str) -> ParseIntError {
+        s.parse::<i32>().unwrap_err()
+    }
+
+    #[test]
+    fn test_parse_left_bound() {
+        assert_eq!("[1".parse(), Ok(LeftBound::Included(1)));
+        assert_eq!("(1".parse(), Ok(LeftBound::Excluded(1)));
+        assert_eq!(" ( -1 ".parse(), Ok(LeftBound::Excluded(-1)));
+        assert_eq!("(-∞".parse(), Ok(LeftBound::<i32>::Unbounded));
+        assert_eq!("(-inf".parse(), Ok(LeftBound::<i32>::Unbounded));
+
+        assert_eq!(
+            "1".parse::<LeftBound<i32>>(),
+            Err(ParseSpanError::new(
+                0,
+                ParseSpanErrorKind::MissingOpeningBracket
+            ))
+        );
+        assert_eq!(
+            " [-∞".parse::<LeftBound<i32>>(),
+            Err(ParseSpanError::new(
+                1,
+                ParseSpanErrorKind::InclusiveInfinity
+            ))
+        );
+        assert_eq!(
+            "[ x".parse::<LeftBound<i32>>(),
+            Err(ParseSpanError::new(
+                2,
+                ParseSpanErrorKind::Key(key_err("x"))
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_right_bound() {
+        assert_eq!("5]".parse(), Ok(RightBound::Included(5)));
+        assert_eq!("5)".parse(), Ok(RightBound::Excluded(5)));
+        assert_eq!(" 5 ) ".parse(), Ok(RightBound::Excluded(5)));
+        assert_eq!("∞)".parse(), Ok(RightBound::<i32>::Unbounded));
+        assert_eq!("+∞)".parse(), Ok(RightBound::<i32>::Unbounded));
+        assert_eq!("inf)".parse(), Ok(RightBound::<i32>::Unbounded));
+        assert_eq!("+inf)".parse(), Ok(RightBound::<i32>::Unbounded));
+
+        assert_eq!(
+            "5".parse::<RightBound<i32>>(),
+            Err(ParseSpanError::new(
+                1,
+                ParseSpanErrorKind::MissingClosingBracket
+            ))
+        );
+        assert_eq!(
+            "∞ ]".parse::<RightBound<i32>>(),
+            Err(ParseSpanError::new(
+                4,
+                ParseSpanErrorKind::InclusiveInfinity
+            ))
+        );
+        assert_eq!(
+            " x]".parse::<RightBound<i32>>(),
+            Err(ParseSpanError::new(
+                1,
+                ParseSpanErrorKind::Key(key_err("x"))
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_span() {
+        assert_eq!("[1, 5)".parse(), Ok(Span::from_range(1..5)));
+        assert_eq!("[1,5]".parse(), Ok(Span::from_range(1..=5)));
+        assert_eq!(
+            "(1, 5]".parse(),
+            Ok(Span::new(LeftBound::Excluded(1), RightBound::Included(5)))
+        );
+        assert_eq!("(-∞, 5]".parse(), Ok(Span::from_range(..=5)));
+        assert_eq!("[1, ∞)".parse(), Ok(Span::from_range(1..)));
+        assert_eq!("(-inf, inf)".parse(), Ok(Span::<i32>::from_range(..)));
+        assert_eq!(" [ -3 , -1 ) ".parse(), Ok(Span::from_range(-3..-1)));
+    }
+
+    #[test]
+    fn test_parse_span_errors() {
+        assert_eq!(
+            "[1 5)".parse::<Span<i32>>(),
+            Err(ParseSpanError::new(5, ParseSpanErrorKind::MissingSeparator))
+        );
+        assert_eq!(
+            "1, 5)".parse::<Span<i32>>(),
+            Err(ParseSpanError::new(
+                0,
+                ParseSpanErrorKind::MissingOpeningBracket
+            ))
+        );
+        assert_eq!(
+            "[1, 5".parse::<Span<i32>>(),
+            Err(ParseSpanError::new(
+                5,
+                ParseSpanErrorKind::MissingClosingBracket
+            ))
+        );
+        assert_eq!(
+            "[1, ∞]".parse::<Span<i32>>(),
+            Err(ParseSpanError::new(
+                7,
+                ParseSpanErrorKind::InclusiveInfinity
+            ))
+        );
+        assert_eq!(
+            "[a, 5)".parse::<Span<i32>>(),
+            Err(ParseSpanError::new(
+                1,
+                ParseSpanErrorKind::Key(key_err("a"))
+            ))
+        );
+        assert_eq!(
+            "[1,  b)".parse::<Span<i32>>(),
+            Err(ParseSpanError::new(
+                5,
+                ParseSpanErrorKind::Key(key_err("b"))
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_span_display_roundtrip() {
+        let spans = [
+            Span::from_range(1..5),
+            Span::from_range(1..=5),
+            Span::new(LeftBound::Excluded(1), RightBound::Excluded(5)),
+            Span::from_range(..5),
+            Span::from_range(1..),
+            Span::from_range(..),
+        ];
+
+        for span in spans {
+            assert_eq!(span.to_string().parse(), Ok(span));
+        }
+    }
+
+    #[test]
+    fn test_parse_error_display() {
+        let err = "[1, x)".parse::<Span<i32>>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid key: invalid digit found in string at position 4"
+        );
+        assert!(err.source().is_some());
+
+        let err = "[1 5)".parse::<Span<i32>>().unwrap_err();
+        assert_eq!(err.to_string(), "expected ',' at position 5");
+        assert!(err.source().is_none());
+    }
+}
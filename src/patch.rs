@@ -0,0 +1,343 @@
+//! Patches: lists of span level changes that can be shipped, applied to a [`SpanMap`] and undone.
+
+use std::ops::RangeBounds;
+
+use crate::Span;
+use crate::SpanError;
+use crate::SpanMap;
+
+/// A single change of a [`SpanPatch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PatchOp<K, V>
+where
+    K: Ord,
+{
+    /// Inserts the value into the span, as [`SpanMap::insert_span`] does.
+    Insert(Span<K>, V),
+
+    /// Removes the value from the span, as [`SpanMap::remove_span`] does.
+    Remove(Span<K>, V),
+}
+
+impl<K, V> PatchOp<K, V>
+where
+    K: Ord,
+{
+    /// Returns the operation that undoes this one, if the value was absent from, respectively
+    /// present in, the whole span before.
+    pub fn invert(self) -> Self {
+        match self {
+            PatchOp::Insert(span, value) => PatchOp::Remove(span, value),
+            PatchOp::Remove(span, value) => PatchOp::Insert(span, value),
+        }
+    }
+
+    /// Returns the span of the operation.
+    pub fn span(&self) -> &Span<K> {
+        match self {
+            PatchOp::Insert(span, _) | PatchOp::Remove(span, _) => span,
+        }
+    }
+}
+
+/// An ordered list of changes to apply to a [`SpanMap`] with [`SpanMap::apply`].
+///
+/// A patch is usually built with [`SpanPatch::diff`], which gives the changes that turn one map
+/// into another, so that only the changes have to be stored or sent. With the `serde` feature,
+/// a patch can be serialized.
+///
+/// # Examples
+/// ```
+/// # use span_map::SpanMap;
+/// # use span_map::SpanPatch;
+///
+/// let mut old = SpanMap::new();
+/// old.insert(0..10, "a");
+///
+/// let mut new = old.clone();
+/// new.remove(0..5, "a");
+/// new.insert(5..15, "b");
+///
+/// let patch = SpanPatch::diff(&old, &new);
+///
+/// let mut map = old.clone();
+/// map.apply(&patch).unwrap();
+/// assert_eq!(map, new);
+///
+/// map.apply(&patch.invert()).unwrap();
+/// assert_eq!(map, old);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpanPatch<K, V>
+where
+    K: Ord,
+{
+    ops: Vec<PatchOp<K, V>>,
+}
+
+impl<K, V> Default for SpanPatch<K, V>
+where
+    K: Ord,
+{
+    fn default() -> Self {
+        Self { ops: Vec::new() }
+    }
+}
+
+impl<K, V> SpanPatch<K, V>
+where
+    K: Clone + Ord,
+    V: Clone + Ord,
+{
+    /// Creates an empty patch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the patch that turns `old` into `new`.
+    ///
+    /// Every operation of the returned patch changes the map it is applied to over its whole
+    /// span, thus [`SpanPatch::invert`] undoes it exactly.
+    pub fn diff(old: &SpanMap<K, V>, new: &SpanMap<K, V>) -> Self {
+        let mut patch = Self::new();
+        for (span, added, removed) in SpanMap::diff(old, new) {
+            for value in removed {
+                patch.push(PatchOp::Remove(span.clone(), value));
+            }
+            for value in added {
+                patch.push(PatchOp::Insert(span.clone(), value));
+            }
+        }
+        patch
+    }
+
+    /// Appends an operation that inserts `value` into `range`.
+    pub fn insert<R>(&mut self, range: R, value: V)
+    where
+        R: RangeBounds<K>,
+    {
+        self.push(PatchOp::Insert(Span::from_range(range), value));
+    }
+
+    /// Appends an operation that removes `value` from `range`.
+    pub fn remove<R>(&mut self, range: R, value: V)
+    where
+        R: RangeBounds<K>,
+    {
+        self.push(PatchOp::Remove(Span::from_range(range), value));
+    }
+
+    /// Appends an operation.
+    pub fn push(&mut self, op: PatchOp<K, V>) {
+        self.ops.push(op);
+    }
+
+    /// Returns the operations, in the order they are applied.
+    pub fn ops(&self) -> &[PatchOp<K, V>] {
+        &self.ops
+    }
+
+    /// Returns the number of operations.
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Returns `true` if the patch has no operation.
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Returns an error if any operation has an empty or inverted span.
+    pub(crate) fn validate(&self) -> Result<(), SpanError> {
+        for op in &self.ops {
+            op.span().validate()?;
+        }
+        Ok(())
+    }
+
+    /// Returns the inverse operations, in reverse order.
+    ///
+    /// This undoes the patch only if every operation changes the whole of its span, as for a
+    /// patch built by [`SpanPatch::diff`]: the patch does not record whether an inserted value
+    /// was already present. To roll back any patch, use the one returned by
+    /// [`SpanMap::apply`].
+    pub fn invert(&self) -> Self {
+        Self {
+            ops: self
+                .ops
+                .iter()
+                .rev()
+                .cloned()
+                .map(PatchOp::invert)
+                .collect(),
+        }
+    }
+}
+
+impl<K, V> SpanMap<K, V>
+where
+    K: Clone + Ord,
+    V: Clone + Ord,
+{
+    /// Applies every operation of `patch` in order, merging segments once at the end, and
+    /// returns the patch that undoes it.
+    ///
+    /// The returned patch is built from the changes actually made, see
+    /// [`SpanMap::apply_tracked`], so applying it restores the map exactly, even if `patch`
+    /// inserted values that were already present or removed values that were absent.
+    ///
+    /// The patch is applied atomically: if any operation has an empty or inverted span, an
+    /// error is returned and the map is left unchanged.
+    ///
+    /// # Examples
+    /// ```
+    /// # use span_map::SpanError;
+    /// # use span_map::SpanMap;
+    /// # use span_map::SpanPatch;
+    ///
+    /// let mut map = SpanMap::new();
+    /// map.insert(0..5, "a");
+    /// let before = map.clone();
+    ///
+    /// let mut patch = SpanPatch::new();
+    /// patch.insert(0..10, "a");
+    ///
+    /// let undo = map.apply(&patch).unwrap();
+    /// map.apply(&undo).unwrap();
+    /// assert_eq!(map, before);
+    ///
+    /// patch.remove(5..5, "a");
+    /// assert_eq!(map.apply(&patch), Err(SpanError::Empty));
+    /// assert_eq!(map, before);
+    /// ```
+    pub fn apply(&mut self, patch: &SpanPatch<K, V>) -> Result<SpanPatch<K, V>, SpanError> {
+        let changes = self.apply_tracked(patch)?;
+
+        // Every change was actually made over its whole span, thus inverting it is exact.
+        let done: SpanPatch<K, V> = changes.into_iter().map(PatchOp::from).collect();
+        Ok(done.invert())
+    }
+}
+
+impl<K, V> FromIterator<PatchOp<K, V>> for SpanPatch<K, V>
+where
+    K: Ord,
+{
+    fn from_iter<I: IntoIterator<Item = PatchOp<K, V>>>(iter: I) -> Self {
+        Self {
+            ops: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_in_order() {
+        let mut patch = SpanPatch::new();
+        patch.insert(0..10, 1);
+        patch.remove(2..4, 1);
+        patch.insert(3..6, 1);
+        assert_eq!(patch.len(), 3);
+
+        let mut map = SpanMap::new();
+        map.apply(&patch).unwrap();
+
+        let mut want = SpanMap::new();
+        want.insert(0..2, 1);
+        want.insert(3..10, 1);
+        assert_eq!(map, want);
+
+        map.apply(&SpanPatch::new()).unwrap();
+        assert_eq!(map, want);
+    }
+
+    #[test]
+    fn test_apply_invalid_is_atomic() {
+        let mut map = SpanMap::new();
+        map.insert(0..10, 1);
+        let before = map.clone();
+
+        let mut patch = SpanPatch::new();
+        patch.remove(0..10, 1);
+        #[allow(clippy::reversed_empty_ranges)]
+        patch.insert(5..1, 2);
+
+        assert_eq!(map.apply(&patch), Err(SpanError::Inverted));
+        assert_eq!(map, before);
+    }
+
+    #[test]
+    fn test_diff_and_invert() {
+        let mut old = SpanMap::new();
+        old.insert(..0, 1);
+        old.insert(0..10, 2);
+        old.insert(5..20, 3);
+
+        let mut new = SpanMap::new();
+        new.insert(3..8, 2);
+        new.insert(15.., 3);
+        new.insert(..=0, 4);
+
+        let patch = SpanPatch::diff(&old, &new);
+        assert!(SpanPatch::diff(&old, &old).is_empty());
+
+        let mut map = old.clone();
+        map.apply(&patch).unwrap();
+        assert_eq!(map, new);
+
+        map.apply(&patch.invert()).unwrap();
+        assert_eq!(map, old);
+
+        assert_eq!(patch.invert().invert(), patch);
+    }
+
+    #[test]
+    fn test_apply_returns_exact_undo() {
+        let mut map = SpanMap::new();
+        map.insert(0..10, 1);
+        map.insert(5..15, 2);
+        let before = map.clone();
+
+        // Inserts values that are partly present, and removes values that are partly absent
+        let mut patch = SpanPatch::new();
+        patch.insert(5..20, 1);
+        patch.remove(..8, 2);
+        patch.insert(3..4, 3);
+        patch.remove(3..4, 3);
+        patch.remove(12.., 1);
+
+        let mut want = map.clone();
+        for op in patch.ops().iter().cloned() {
+            match op {
+                PatchOp::Insert(span, value) => want.insert_span(span, value),
+                PatchOp::Remove(span, value) => want.remove_span(span, value),
+            }
+        }
+
+        let undo = map.apply(&patch).unwrap();
+        assert_eq!(map, want);
+
+        let redo = map.apply(&undo).unwrap();
+        assert_eq!(map, before);
+
+        map.apply(&redo).unwrap();
+        assert_eq!(map, want);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let mut patch = SpanPatch::new();
+        patch.insert(0..10, "a".to_string());
+        patch.remove(..=3, "b".to_string());
+
+        let json = serde_json::to_string(&patch).unwrap();
+        let back: SpanPatch<i32, String> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, patch);
+    }
+}
@@ -0,0 +1,192 @@
+//! ASCII rendering of a [`SpanMap`] for debugging.
+
+use std::collections::BTreeSet;
+use std::fmt::Display;
+
+use crate::bounds::LeftBound;
+use crate::bounds::RightBound;
+use crate::SpanMap;
+
+impl<K, V> SpanMap<K, V>
+where
+    K: Clone + Ord + Display,
+    V: Clone + Ord + Display,
+{
+    /// Draws the map as a timeline, one lane per value, with the boundary keys labelled below.
+    ///
+    /// Boundary keys are placed at evenly spaced columns in key order, regardless of the
+    /// distance between them. Included bounds are drawn as `[` and `]`, excluded bounds as `(`
+    /// and `)`, unbounded ends as `<` and `>`. A column where two bounds of the same lane meet,
+    /// e.g., a single-key span, is drawn as `|`.
+    ///
+    /// `width` is the number of columns of the key axis. It is widened if needed so that every
+    /// boundary key gets a column of its own. An empty map renders as an empty string.
+    ///
+    /// # Examples
+    /// ```
+    /// # use span_map::SpanMap;
+    ///
+    /// let mut map = SpanMap::new();
+    /// map.insert(1..=5, "a");
+    /// map.insert(3..7, "b");
+    ///
+    /// assert_eq!(
+    ///     map.render_ascii(15),
+    ///     [
+    ///         "a: .[-------].....",
+    ///         "b: .....[-------).",
+    ///         "    1   3   5   7",
+    ///     ]
+    ///     .join("\n")
+    /// );
+    /// ```
+    pub fn render_ascii(&self, width: usize) -> String {
+        let values: BTreeSet<&V> = self.m.values().flatten().collect();
+        if values.is_empty() {
+            return String::new();
+        }
+
+        let lanes: Vec<(String, Vec<_>)> = values
+            .into_iter()
+            .map(|v| (v.to_string(), self.spans_of(v).iter().collect()))
+            .collect();
+
+        let keys: BTreeSet<&K> = lanes
+            .iter()
+            .flat_map(|(_label, spans)| spans)
+            .flat_map(|span| [span.left().key(), span.right().key()])
+            .flatten()
+            .collect();
+        let keys: Vec<&K> = keys.into_iter().collect();
+
+        // Column 0 and the last column are reserved for unbounded ends.
+        let width = width.max(keys.len() + 2);
+        let column = |key: &K| {
+            // Safe unwrap(): every key of every span is in `keys`
+            let i = keys.binary_search(&key).unwrap();
+            if keys.len() == 1 {
+                1
+            } else {
+                1 + i * (width - 3) / (keys.len() - 1)
+            }
+        };
+
+        let label_width = lanes
+            .iter()
+            .map(|(l, _)| l.chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut lines = Vec::new();
+
+        for (label, spans) in &lanes {
+            let mut row = vec!['.'; width];
+            let mut put = |col: usize, c: char| {
+                row[col] = if matches!(row[col], '.' | '-') {
+                    c
+                } else {
+                    '|'
+                };
+            };
+
+            for span in spans {
+                let (l, lc) = match span.left() {
+                    LeftBound::Unbounded => (0, '<'),
+                    LeftBound::Included(k) => (column(k), '['),
+                    LeftBound::Excluded(k) => (column(k), '('),
+                };
+                let (r, rc) = match span.right() {
+                    RightBound::Excluded(k) => (column(k), ')'),
+                    RightBound::Included(k) => (column(k), ']'),
+                    RightBound::Unbounded => (width - 1, '>'),
+                };
+
+                for col in l + 1..r {
+                    put(col, '-');
+                }
+                put(l, lc);
+                put(r, rc);
+            }
+
+            let row: String = row.into_iter().collect();
+            lines.push(format!("{:>w$}: {}", label, row, w = label_width));
+        }
+
+        // Key labels go on the first axis row where they do not overlap the previous label.
+        let mut axis: Vec<Vec<char>> = Vec::new();
+        for key in &keys {
+            let text: Vec<char> = key.to_string().chars().collect();
+            let start = label_width + 2 + column(key);
+
+            let row = match axis.iter().position(|row| row.len() < start) {
+                Some(i) => &mut axis[i],
+                None => {
+                    axis.push(Vec::new());
+                    axis.last_mut().unwrap()
+                }
+            };
+            row.resize(start, ' ');
+            row.extend(text);
+        }
+
+        lines.extend(axis.into_iter().map(|row| row.into_iter().collect()));
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bounds::LeftBound;
+    use crate::bounds::RightBound;
+    use crate::Span;
+    use crate::SpanMap;
+
+    #[test]
+    fn test_render_empty() {
+        let mut map = SpanMap::<i32, &str>::new();
+        assert_eq!(map.render_ascii(20), "");
+
+        map.insert(1..5, "a");
+        map.remove(.., "a");
+        assert_eq!(map.render_ascii(20), "");
+    }
+
+    #[test]
+    fn test_render_bounds() {
+        let mut map = SpanMap::<i32, &str>::new();
+        map.insert(..2, "a");
+        map.insert(4..6, "a");
+        map.insert_span(
+            Span::new(LeftBound::Excluded(2), RightBound::Unbounded),
+            "bb",
+        );
+
+        assert_eq!(
+            map.render_ascii(9),
+            [" a: <)..[--).", "bb: .(------>", "     2  4  6"].join("\n")
+        );
+    }
+
+    #[test]
+    fn test_render_single_key_and_narrow_width() {
+        let mut map = SpanMap::<i32, &str>::new();
+        map.insert(3..=3, "a");
+        map.insert(1..3, "b");
+
+        // The width is widened to fit every boundary key
+        assert_eq!(
+            map.render_ascii(0),
+            ["a: ..|.", "b: .[).", "    1", "     3"].join("\n")
+        );
+    }
+
+    #[test]
+    fn test_render_long_labels() {
+        let mut map = SpanMap::<i32, &str>::new();
+        map.insert(100..1000, "a");
+
+        assert_eq!(
+            map.render_ascii(6),
+            ["a: .[--).", "    100", "       1000"].join("\n")
+        );
+    }
+}
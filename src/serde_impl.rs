@@ -0,0 +1,147 @@
+//! `Serialize` and `Deserialize` for [`SpanMap`], enabled by the `serde` feature.
+//!
+//! A map is serialized as the list of its non-empty segments, each as `{span, values}`, instead
+//! of the internal boundary tree. Deserializing inserts every segment into a new map, so the
+//! invariants hold whatever the input: overlapping or adjacent segments are merged, and a segment
+//! with an empty span is rejected.
+
+use std::collections::BTreeSet;
+
+use serde::de;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+
+use crate::Span;
+use crate::SpanMap;
+
+#[derive(Serialize)]
+struct SegmentRef<'a, K, V>
+where
+    K: Ord,
+{
+    span: Span<K>,
+    values: &'a BTreeSet<V>,
+}
+
+#[derive(Deserialize)]
+struct Segment<K, V>
+where
+    K: Ord,
+    V: Ord,
+{
+    span: Span<K>,
+    values: BTreeSet<V>,
+}
+
+impl<K, V> Serialize for SpanMap<K, V>
+where
+    K: Clone + Ord + Serialize,
+    V: Clone + Ord + Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let segments = self
+            .iter()
+            .map(|(span, values)| SegmentRef { span, values });
+
+        serializer.collect_seq(segments)
+    }
+}
+
+impl<'de, K, V> Deserialize<'de> for SpanMap<K, V>
+where
+    K: Clone + Ord + Deserialize<'de>,
+    V: Clone + Ord + Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let segments = Vec::<Segment<K, V>>::deserialize(deserializer)?;
+
+        let mut map = SpanMap::new();
+        for Segment { span, values } in segments {
+            span.validate().map_err(de::Error::custom)?;
+
+            for v in values {
+                map.insert_span(span.clone(), v);
+            }
+        }
+
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bounds::LeftBound;
+    use crate::bounds::RightBound;
+    use crate::Span;
+    use crate::SpanMap;
+
+    #[test]
+    fn test_serialize() {
+        let mut map = SpanMap::<i32, &str>::new();
+        assert_eq!(serde_json::to_string(&map).unwrap(), "[]");
+
+        map.insert(1..5, "a");
+        map.insert(3.., "b");
+
+        assert_eq!(
+            serde_json::to_string(&map).unwrap(),
+            concat!(
+                r#"[{"span":{"left":{"Included":1},"right":{"Excluded":3}},"values":["a"]},"#,
+                r#"{"span":{"left":{"Included":3},"right":{"Excluded":5}},"values":["a","b"]},"#,
+                r#"{"span":{"left":{"Included":5},"right":"Unbounded"},"values":["b"]}]"#,
+            )
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut map = SpanMap::<i32, String>::new();
+        map.insert(..=1, "a".to_string());
+        map.insert(3..7, "b".to_string());
+        map.insert(5.., "c".to_string());
+
+        let json = serde_json::to_string(&map).unwrap();
+        let back: SpanMap<i32, String> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, map);
+
+        let span = Span::new(LeftBound::Excluded(1), RightBound::Included(3));
+        let json = serde_json::to_string(&span).unwrap();
+        assert_eq!(json, r#"{"left":{"Excluded":1},"right":{"Included":3}}"#);
+        assert_eq!(serde_json::from_str::<Span<i32>>(&json).unwrap(), span);
+    }
+
+    #[test]
+    fn test_deserialize_canonicalizes() {
+        // Overlapping and adjacent segments are merged into the canonical form
+        let json = concat!(
+            r#"[{"span":{"left":{"Included":1},"right":{"Excluded":5}},"values":["a"]},"#,
+            r#"{"span":{"left":{"Included":3},"right":{"Excluded":8}},"values":["a"]},"#,
+            r#"{"span":{"left":{"Included":8},"right":{"Excluded":9}},"values":["a"]},"#,
+            r#"{"span":{"left":"Unbounded","right":{"Excluded":0}},"values":[]}]"#,
+        );
+        let map: SpanMap<i32, String> = serde_json::from_str(json).unwrap();
+
+        let mut want = SpanMap::new();
+        want.insert(1..9, "a".to_string());
+        assert_eq!(map, want);
+    }
+
+    #[test]
+    fn test_deserialize_rejects_empty_span() {
+        let json = r#"[{"span":{"left":{"Included":5},"right":{"Excluded":1}},"values":["a"]}]"#;
+        let err = serde_json::from_str::<SpanMap<i32, String>>(json).unwrap_err();
+        assert!(err.to_string().contains("greater than"), "{}", err);
+
+        let json = r#"[{"span":{"left":{"Excluded":1},"right":{"Excluded":1}},"values":["a"]}]"#;
+        let err = serde_json::from_str::<SpanMap<i32, String>>(json).unwrap_err();
+        assert!(err.to_string().contains("no key"), "{}", err);
+    }
+}
@@ -1,12 +1,36 @@
+//! A continuous range of keys, bounded by a [`LeftBound`] and a [`RightBound`].
+
 use std::cmp::Ordering;
 use std::fmt;
 use std::fmt::Formatter;
+use std::ops::Bound;
 use std::ops::RangeBounds;
 
 use crate::bounds::LeftBound;
 use crate::bounds::RightBound;
+use crate::SpanError;
+use crate::Step;
 
+/// A continuous range of keys, such as `[1, 5)` or `(-∞, 3]`.
+///
+/// Unlike `std::ops::Range` and friends, a `Span` has a single type for all combinations of
+/// included, excluded and unbounded ends. It implements `RangeBounds`, so it can be passed
+/// wherever a range is accepted, e.g., [`SpanMap::insert`](crate::SpanMap::insert).
+///
+/// # Examples
+/// ```
+/// # use span_map::Span;
+///
+/// let a = Span::from_range(1..5);
+/// let b = Span::from_range(3..=8);
+///
+/// assert!(a.overlaps(&b));
+/// assert_eq!(a.intersection(&b), Some(Span::from_range(3..5)));
+/// assert_eq!(a.hull(&b), Span::from_range(1..=8));
+/// assert_eq!(a.to_string(), "[1, 5)");
+/// ```
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Span<T>
 where
     T: Ord,
@@ -29,10 +53,12 @@ impl<T> Span<T>
 where
     T: Ord,
 {
+    /// Creates a span from its two bounds.
     pub fn new(left: LeftBound<T>, right: RightBound<T>) -> Self {
         Self { left, right }
     }
 
+    /// Creates a span from any standard range, e.g., `1..5`, `..=3` or `2..`.
     pub fn from_range<R>(range: R) -> Self
     where
         T: Clone,
@@ -43,6 +69,209 @@ where
             range.end_bound().cloned().into(),
         )
     }
+
+    /// Returns the left bound of the span.
+    pub fn left(&self) -> &LeftBound<T> {
+        &self.left
+    }
+
+    /// Returns the right bound of the span.
+    pub fn right(&self) -> &RightBound<T> {
+        &self.right
+    }
+
+    /// Consumes the span and returns its left and right bounds.
+    pub fn into_bounds(self) -> (LeftBound<T>, RightBound<T>) {
+        (self.left, self.right)
+    }
+
+    /// Returns `true` if `key` lies within the span.
+    ///
+    /// # Examples
+    /// ```
+    /// # use span_map::Span;
+    ///
+    /// let span = Span::from_range(1..5);
+    /// assert!(!span.contains(&0));
+    /// assert!(span.contains(&1));
+    /// assert!(!span.contains(&5));
+    /// ```
+    pub fn contains(&self, key: &T) -> bool {
+        self.left.admits(key) && self.right.admits(key)
+    }
+
+    /// Returns `true` if the two spans have at least one key in common.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.left <= other.right
+            && other.left <= self.right
+            && !self.is_empty()
+            && !other.is_empty()
+    }
+
+    /// Returns `true` if the two spans do not overlap, but together form a continuous span
+    /// without gap, e.g., `[1, 3)` and `[3, 5]`.
+    pub fn is_adjacent(&self, other: &Self) -> bool
+    where
+        T: Clone,
+    {
+        if self.is_empty() || other.is_empty() {
+            return false;
+        }
+
+        self.right.adjacent_left().as_ref() == Some(&other.left)
+            || other.right.adjacent_left().as_ref() == Some(&self.left)
+    }
+
+    /// Returns the keys that are in both spans, or `None` if the spans do not overlap.
+    pub fn intersection(&self, other: &Self) -> Option<Self>
+    where
+        T: Clone,
+    {
+        let span = Self::new(
+            (&self.left).max(&other.left).clone(),
+            (&self.right).min(&other.right).clone(),
+        );
+
+        if span.is_empty() {
+            None
+        } else {
+            Some(span)
+        }
+    }
+
+    /// Returns the smallest span that contains both spans, including any gap between them.
+    ///
+    /// An empty span contributes no key: the hull of an empty span and another span is the
+    /// other span.
+    pub fn hull(&self, other: &Self) -> Self
+    where
+        T: Clone,
+    {
+        if self.is_empty() {
+            return other.clone();
+        }
+        if other.is_empty() {
+            return self.clone();
+        }
+
+        Self::new(
+            (&self.left).min(&other.left).clone(),
+            (&self.right).max(&other.right).clone(),
+        )
+    }
+
+    /// Returns the keys of `self` that are not in `other`.
+    ///
+    /// The result consists of up to two spans: the part before `other` and the part after it.
+    /// If `other` is empty, `self` is returned as the part before it.
+    ///
+    /// # Examples
+    /// ```
+    /// # use span_map::Span;
+    ///
+    /// let a = Span::from_range(1..10);
+    ///
+    /// assert_eq!(
+    ///     a.difference(&Span::from_range(3..5)),
+    ///     (Some(Span::from_range(1..3)), Some(Span::from_range(5..10)))
+    /// );
+    /// assert_eq!(
+    ///     a.difference(&Span::from_range(..5)),
+    ///     (None, Some(Span::from_range(5..10)))
+    /// );
+    /// assert_eq!(a.difference(&Span::from_range(..)), (None, None));
+    /// ```
+    pub fn difference(&self, other: &Self) -> (Option<Self>, Option<Self>)
+    where
+        T: Clone,
+    {
+        if !self.overlaps(other) {
+            return if self.is_empty() {
+                (None, None)
+            } else if other.is_empty() || self.right < other.left {
+                (Some(self.clone()), None)
+            } else {
+                (None, Some(self.clone()))
+            };
+        }
+
+        let before = other.left.adjacent_right().map(|right| {
+            let right = (&self.right).min(&right).clone();
+            Self::new(self.left.clone(), right)
+        });
+
+        let after = other.right.adjacent_left().map(|left| {
+            let left = (&self.left).max(&left).clone();
+            Self::new(left, self.right.clone())
+        });
+
+        (
+            before.filter(|s| !s.is_empty()),
+            after.filter(|s| !s.is_empty()),
+        )
+    }
+
+    /// Returns `true` if the span contains no key, e.g., `[5, 1]` or `[5, 5)`.
+    pub fn is_empty(&self) -> bool {
+        self.left > self.right
+    }
+
+    /// Returns an error describing why the span is empty, or `Ok(())` if it is not.
+    pub fn validate(&self) -> Result<(), SpanError> {
+        if !self.is_empty() {
+            return Ok(());
+        }
+
+        match (self.left.key(), self.right.key()) {
+            (Some(l), Some(r)) if l > r => Err(SpanError::Inverted),
+            _ => Err(SpanError::Empty),
+        }
+    }
+
+    /// Returns `true` if the span contains no key of a discrete key type, e.g., `(5, 6)`, or `..0`
+    /// for `u8`, although it is not empty.
+    pub(crate) fn has_no_key(&self) -> bool
+    where
+        T: Step,
+    {
+        let span = self.clone().canonical();
+
+        // A bound that is still excluded is past the end of the key domain.
+        span.is_empty()
+            || matches!(span.left, LeftBound::Excluded(_))
+            || matches!(span.right, RightBound::Excluded(_))
+    }
+
+    /// Converts both bounds into the equivalent included bounds, for discrete keys.
+    ///
+    /// See [`LeftBound::canonical`] and [`RightBound::canonical`].
+    pub fn canonical(self) -> Self
+    where
+        T: Step,
+    {
+        Self::new(self.left.canonical(), self.right.canonical())
+    }
+}
+
+impl<T> RangeBounds<T> for Span<T>
+where
+    T: Ord,
+{
+    fn start_bound(&self) -> Bound<&T> {
+        match &self.left {
+            LeftBound::Unbounded => Bound::Unbounded,
+            LeftBound::Included(t) => Bound::Included(t),
+            LeftBound::Excluded(t) => Bound::Excluded(t),
+        }
+    }
+
+    fn end_bound(&self) -> Bound<&T> {
+        match &self.right {
+            RightBound::Unbounded => Bound::Unbounded,
+            RightBound::Included(t) => Bound::Included(t),
+            RightBound::Excluded(t) => Bound::Excluded(t),
+        }
+    }
 }
 
 impl<T> PartialOrd for Span<T>
@@ -120,6 +349,219 @@ mod tests {
         assert_eq!(rng.right, RightBound::Included(5));
     }
 
+    #[allow(clippy::reversed_empty_ranges)]
+    #[test]
+    fn test_is_empty() {
+        assert!(!Span::from_range(1..5).is_empty());
+        assert!(!Span::from_range(5..=5).is_empty());
+        assert!(!Span::<i32>::from_range(..).is_empty());
+        assert!(!Span::from_range(..5).is_empty());
+        assert!(!Span::from_range(5..).is_empty());
+
+        assert!(Span::from_range(5..5).is_empty());
+        assert!(Span::from_range(5..1).is_empty());
+        assert!(Span::from_range(5..=1).is_empty());
+        assert!(Span::new(LeftBound::Excluded(5), RightBound::Included(5)).is_empty());
+        assert!(Span::new(LeftBound::Excluded(5), RightBound::Excluded(5)).is_empty());
+    }
+
+    #[allow(clippy::reversed_empty_ranges)]
+    #[test]
+    fn test_validate() {
+        assert_eq!(Span::from_range(1..5).validate(), Ok(()));
+        assert_eq!(Span::from_range(5..=5).validate(), Ok(()));
+        assert_eq!(Span::<i32>::from_range(..).validate(), Ok(()));
+
+        assert_eq!(Span::from_range(5..5).validate(), Err(SpanError::Empty));
+        assert_eq!(
+            Span::new(LeftBound::Excluded(5), RightBound::Included(5)).validate(),
+            Err(SpanError::Empty)
+        );
+        assert_eq!(Span::from_range(5..1).validate(), Err(SpanError::Inverted));
+        assert_eq!(
+            Span::new(LeftBound::Excluded(5), RightBound::Excluded(1)).validate(),
+            Err(SpanError::Inverted)
+        );
+    }
+
+    #[test]
+    fn test_canonical() {
+        let rng = Span::from_range(1..5).canonical();
+        assert_eq!(rng, Span::from_range(1..=4));
+
+        let rng = Span::new(LeftBound::Excluded(1), RightBound::Unbounded).canonical();
+        assert_eq!(rng, Span::from_range(2..));
+
+        let rng = Span::new(LeftBound::Unbounded, RightBound::Excluded(u8::MIN)).canonical();
+        assert_eq!(
+            rng,
+            Span::new(LeftBound::Unbounded, RightBound::Excluded(0))
+        );
+    }
+
+    #[test]
+    fn test_accessors() {
+        let rng = Span::from_range(1..5);
+        assert_eq!(rng.left(), &LeftBound::Included(1));
+        assert_eq!(rng.right(), &RightBound::Excluded(5));
+        assert_eq!(
+            rng.into_bounds(),
+            (LeftBound::Included(1), RightBound::Excluded(5))
+        );
+    }
+
+    #[test]
+    fn test_contains() {
+        let rng = Span::new(LeftBound::Excluded(1), RightBound::Included(5));
+        assert!(!rng.contains(&1));
+        assert!(rng.contains(&2));
+        assert!(rng.contains(&5));
+        assert!(!rng.contains(&6));
+
+        let rng = Span::<i32>::from_range(..);
+        assert!(rng.contains(&i32::MIN));
+        assert!(rng.contains(&i32::MAX));
+
+        let rng = Span::from_range(3..3);
+        assert!(!rng.contains(&3));
+    }
+
+    #[test]
+    fn test_overlaps() {
+        let a = Span::from_range(1..5);
+
+        assert!(a.overlaps(&Span::from_range(4..8)));
+        assert!(a.overlaps(&Span::from_range(..=1)));
+        assert!(a.overlaps(&Span::from_range(2..3)));
+        assert!(a.overlaps(&Span::from_range(..)));
+
+        assert!(!a.overlaps(&Span::from_range(5..8)));
+        assert!(!a.overlaps(&Span::from_range(..1)));
+        assert!(!a.overlaps(&Span::from_range(3..3)));
+        assert!(!Span::from_range(3..3).overlaps(&Span::from_range(..)));
+    }
+
+    #[test]
+    fn test_is_adjacent() {
+        let a = Span::from_range(1..5);
+
+        assert!(a.is_adjacent(&Span::from_range(5..8)));
+        assert!(a.is_adjacent(&Span::new(LeftBound::Unbounded, RightBound::Excluded(1))));
+        assert!(Span::from_range(1..=5)
+            .is_adjacent(&Span::new(LeftBound::Excluded(5), RightBound::Unbounded)));
+
+        // Overlapping
+        assert!(!a.is_adjacent(&Span::from_range(4..8)));
+        assert!(!Span::from_range(1..=5).is_adjacent(&Span::from_range(5..8)));
+        // Gap
+        assert!(!a.is_adjacent(&Span::new(LeftBound::Excluded(5), RightBound::Unbounded)));
+        // Empty
+        assert!(!a.is_adjacent(&Span::from_range(5..5)));
+    }
+
+    #[test]
+    fn test_intersection() {
+        let a = Span::from_range(1..5);
+
+        assert_eq!(
+            a.intersection(&Span::from_range(3..=8)),
+            Some(Span::from_range(3..5))
+        );
+        assert_eq!(
+            a.intersection(&Span::new(LeftBound::Excluded(2), RightBound::Unbounded)),
+            Some(Span::new(LeftBound::Excluded(2), RightBound::Excluded(5)))
+        );
+        assert_eq!(a.intersection(&Span::from_range(..)), Some(a.clone()));
+        assert_eq!(
+            a.intersection(&Span::from_range(4..=4)),
+            Some(Span::from_range(4..=4))
+        );
+
+        assert_eq!(a.intersection(&Span::from_range(5..8)), None);
+        assert_eq!(a.intersection(&Span::from_range(..1)), None);
+    }
+
+    #[test]
+    fn test_hull() {
+        let a = Span::from_range(1..5);
+
+        assert_eq!(a.hull(&Span::from_range(3..=8)), Span::from_range(1..=8));
+        assert_eq!(a.hull(&Span::from_range(7..8)), Span::from_range(1..8));
+        assert_eq!(a.hull(&Span::from_range(..0)), Span::from_range(..5));
+        assert_eq!(a.hull(&Span::from_range(2..3)), a);
+        assert_eq!(a.hull(&Span::from_range(9..9)), a);
+        assert_eq!(Span::from_range(9..9).hull(&a), a);
+    }
+
+    #[test]
+    fn test_difference() {
+        let a = Span::from_range(1..10);
+
+        // Hole in the middle
+        assert_eq!(
+            a.difference(&Span::new(LeftBound::Excluded(3), RightBound::Included(5))),
+            (
+                Some(Span::from_range(1..=3)),
+                Some(Span::new(LeftBound::Excluded(5), RightBound::Excluded(10)))
+            )
+        );
+
+        // Overlapping one end
+        assert_eq!(
+            a.difference(&Span::from_range(5..)),
+            (Some(Span::from_range(1..5)), None)
+        );
+        assert_eq!(
+            a.difference(&Span::from_range(..=5)),
+            (
+                None,
+                Some(Span::new(LeftBound::Excluded(5), RightBound::Excluded(10)))
+            )
+        );
+
+        // Covering
+        assert_eq!(a.difference(&Span::from_range(0..20)), (None, None));
+        assert_eq!(a.difference(&a), (None, None));
+
+        // Disjoint
+        assert_eq!(
+            a.difference(&Span::from_range(10..20)),
+            (Some(a.clone()), None)
+        );
+        assert_eq!(
+            a.difference(&Span::from_range(..1)),
+            (None, Some(a.clone()))
+        );
+        assert_eq!(
+            a.difference(&Span::from_range(5..5)),
+            (Some(a.clone()), None)
+        );
+
+        // Sharing a bound
+        assert_eq!(
+            a.difference(&Span::from_range(1..=1)),
+            (
+                None,
+                Some(Span::new(LeftBound::Excluded(1), RightBound::Excluded(10)))
+            )
+        );
+    }
+
+    #[test]
+    fn test_range_bounds() {
+        let rng = Span::new(LeftBound::Excluded(1), RightBound::Included(5));
+        assert_eq!(rng.start_bound(), Bound::Excluded(&1));
+        assert_eq!(rng.end_bound(), Bound::Included(&5));
+
+        let rng = Span::<i32>::from_range(..);
+        assert_eq!(rng.start_bound(), Bound::Unbounded);
+        assert_eq!(rng.end_bound(), Bound::Unbounded);
+
+        // Round trip
+        let rng = Span::from_range(1..5);
+        assert_eq!(Span::from_range(rng.clone()), rng);
+    }
+
     #[allow(clippy::neg_cmp_op_on_partial_ord)]
     #[test]
     fn test_partial_ord() {
@@ -0,0 +1,369 @@
+use std::collections::BTreeSet;
+use std::ops::RangeBounds;
+
+use crate::Span;
+use crate::SpanMap;
+
+/// A set of keys, stored as the canonical list of disjoint, maximal spans.
+///
+/// `SpanSet` is a [`SpanMap`] without values: a key is either in the set or not. Inserting
+/// overlapping or adjacent spans merges them into a single span.
+///
+/// # Examples
+/// ```
+/// # use span_map::Span;
+/// # use span_map::SpanSet;
+///
+/// let mut set = SpanSet::new();
+/// set.insert(1..5);
+/// set.insert(3..8);
+/// set.insert(10..12);
+///
+/// assert!(set.contains(&7));
+/// assert!(!set.contains(&8));
+///
+/// let spans: Vec<_> = set.iter().collect();
+/// assert_eq!(
+///     spans,
+///     vec![Span::from_range(1..8), Span::from_range(10..12)]
+/// );
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct SpanSet<K>
+where
+    K: Clone + Ord,
+{
+    m: SpanMap<K, ()>,
+}
+
+impl<K> Default for SpanSet<K>
+where
+    K: Clone + Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K> From<SpanMap<K, ()>> for SpanSet<K>
+where
+    K: Clone + Ord,
+{
+    fn from(m: SpanMap<K, ()>) -> Self {
+        Self { m }
+    }
+}
+
+impl<K> From<SpanSet<K>> for SpanMap<K, ()>
+where
+    K: Clone + Ord,
+{
+    fn from(set: SpanSet<K>) -> Self {
+        set.m
+    }
+}
+
+impl<K> SpanSet<K>
+where
+    K: Clone + Ord,
+{
+    /// Creates a new, empty `SpanSet`.
+    pub fn new() -> Self {
+        Self { m: SpanMap::new() }
+    }
+
+    /// Returns `true` if the set contains no key.
+    pub fn is_empty(&self) -> bool {
+        self.m.content_start().is_none()
+    }
+
+    /// Returns `true` if `key` is in the set.
+    pub fn contains(&self, key: &K) -> bool {
+        self.m.get(key).next().is_some()
+    }
+
+    /// Adds all keys in the given range to the set.
+    pub fn insert<R>(&mut self, range: R)
+    where
+        R: RangeBounds<K>,
+    {
+        self.insert_span(Span::from_range(range));
+    }
+
+    /// Removes all keys in the given range from the set.
+    pub fn remove<R>(&mut self, range: R)
+    where
+        R: RangeBounds<K>,
+    {
+        self.remove_span(Span::from_range(range));
+    }
+
+    /// Same as [`SpanSet::insert`], with the range given as a [`Span`].
+    pub fn insert_span(&mut self, span: Span<K>) {
+        self.m.insert_span(span, ());
+    }
+
+    /// Same as [`SpanSet::remove`], with the range given as a [`Span`].
+    pub fn remove_span(&mut self, span: Span<K>) {
+        self.m.remove_span(span, ());
+    }
+
+    /// Returns an iterator over the maximal spans of the set, in key order.
+    pub fn iter(&self) -> impl Iterator<Item = Span<K>> + '_ {
+        self.m.iter().map(|(span, _set)| span)
+    }
+
+    /// Returns the set of keys that are in `self` or in `other`.
+    pub fn union(&self, other: &Self) -> Self {
+        let mut m = self.m.clone();
+        m.union_with(&other.m);
+        Self { m }
+    }
+
+    /// Returns the set of keys that are in both `self` and `other`.
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut res = self.clone();
+        for span in other.complement().iter() {
+            res.remove_span(span);
+        }
+        res
+    }
+
+    /// Returns the set of keys that are not in `self`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use span_map::Span;
+    /// # use span_map::SpanSet;
+    ///
+    /// let mut set = SpanSet::new();
+    /// set.insert(1..5);
+    ///
+    /// let spans: Vec<_> = set.complement().iter().collect();
+    /// assert_eq!(spans, vec![Span::from_range(..1), Span::from_range(5..)]);
+    /// ```
+    pub fn complement(&self) -> Self {
+        self.m.complement()
+    }
+}
+
+impl<K, V> SpanMap<K, V>
+where
+    K: Clone + Ord,
+    V: Clone + Ord,
+{
+    /// Returns the set of keys at which `value` is present.
+    ///
+    /// # Examples
+    /// ```
+    /// # use span_map::Span;
+    /// # use span_map::SpanMap;
+    ///
+    /// let mut map = SpanMap::new();
+    /// map.insert(1..5, "a");
+    /// map.insert(3..8, "b");
+    /// map.insert(6..9, "a");
+    ///
+    /// let spans: Vec<_> = map.spans_of(&"a").iter().collect();
+    /// assert_eq!(spans, vec![Span::from_range(1..5), Span::from_range(6..9)]);
+    /// ```
+    pub fn spans_of(&self, value: &V) -> SpanSet<K> {
+        self.keys_where(|set| set.contains(value))
+    }
+
+    /// Returns the set of keys at which no value is present.
+    ///
+    /// # Examples
+    /// ```
+    /// # use span_map::bounds::{LeftBound, RightBound};
+    /// # use span_map::Span;
+    /// # use span_map::SpanMap;
+    ///
+    /// let mut busy = SpanMap::new();
+    /// busy.insert(9..12, "meeting");
+    /// busy.insert(14..=15, "review");
+    ///
+    /// let free: Vec<_> = busy.complement().iter().collect();
+    /// assert_eq!(
+    ///     free,
+    ///     vec![
+    ///         Span::from_range(..9),
+    ///         Span::from_range(12..14),
+    ///         Span::new(LeftBound::Excluded(15), RightBound::Unbounded),
+    ///     ]
+    /// );
+    /// ```
+    pub fn complement(&self) -> SpanSet<K> {
+        self.keys_where(|set| set.is_empty())
+    }
+
+    /// Returns the set of keys whose value set satisfies `f`.
+    fn keys_where(&self, f: impl Fn(&BTreeSet<V>) -> bool) -> SpanSet<K> {
+        let m = self
+            .m
+            .iter()
+            .map(|(b, set)| {
+                let present = if f(set) {
+                    BTreeSet::from([()])
+                } else {
+                    BTreeSet::new()
+                };
+                (b.clone(), present)
+            })
+            .collect();
+
+        let mut m = SpanMap { m };
+        m.merge_all_adjacent();
+
+        SpanSet { m }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounds::LeftBound;
+    use crate::bounds::RightBound;
+
+    fn set_of(ranges: &[std::ops::Range<i32>]) -> SpanSet<i32> {
+        let mut set = SpanSet::new();
+        for r in ranges {
+            set.insert(r.clone());
+        }
+        set
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut set = SpanSet::new();
+        assert!(set.is_empty());
+
+        set.insert(1..5);
+        set.insert(5..8);
+        set.insert(10..=12);
+        assert!(!set.is_empty());
+
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            vec![Span::from_range(1..8), Span::from_range(10..=12),]
+        );
+
+        set.remove(3..=4);
+        set.remove(10..=12);
+
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            vec![
+                Span::from_range(1..3),
+                Span::new(LeftBound::Excluded(4), RightBound::Excluded(8)),
+            ]
+        );
+
+        set.remove(..);
+        assert!(set.is_empty());
+        assert_eq!(set, SpanSet::new());
+    }
+
+    #[test]
+    fn test_contains() {
+        let set = set_of(&[1..5, 7..9]);
+
+        assert!(!set.contains(&0));
+        assert!(set.contains(&1));
+        assert!(set.contains(&4));
+        assert!(!set.contains(&5));
+        assert!(set.contains(&8));
+        assert!(!set.contains(&9));
+    }
+
+    #[test]
+    fn test_union() {
+        let a = set_of(&[1..5, 10..15]);
+        let b = set_of(&[3..7, 15..20, 30..40]);
+
+        assert_eq!(a.union(&b), set_of(&[1..7, 10..20, 30..40]));
+        assert_eq!(a.union(&SpanSet::new()), a);
+        assert_eq!(SpanSet::new().union(&a), a);
+    }
+
+    #[test]
+    fn test_intersection() {
+        let a = set_of(&[1..5, 10..15]);
+        let b = set_of(&[3..12, 14..20]);
+
+        assert_eq!(a.intersection(&b), set_of(&[3..5, 10..12, 14..15]));
+        assert_eq!(a.intersection(&SpanSet::new()), SpanSet::new());
+        assert_eq!(a.intersection(&a.complement()), SpanSet::new());
+    }
+
+    #[test]
+    fn test_complement() {
+        let set = set_of(&[1..5, 10..15]);
+
+        assert_eq!(
+            set.complement().iter().collect::<Vec<_>>(),
+            vec![
+                Span::from_range(..1),
+                Span::from_range(5..10),
+                Span::from_range(15..),
+            ]
+        );
+        assert_eq!(set.complement().complement(), set);
+
+        let mut all = SpanSet::<i32>::new();
+        all.insert(..);
+        assert_eq!(SpanSet::new().complement(), all);
+        assert_eq!(all.complement(), SpanSet::new());
+    }
+
+    #[test]
+    fn test_span_map_complement() {
+        let mut map = SpanMap::<i32, &str>::new();
+        assert_eq!(map.complement(), SpanSet::new().complement());
+
+        map.insert(1..5, "a");
+        map.insert(3..=8, "b");
+        map.insert_span(
+            Span::new(LeftBound::Excluded(10), RightBound::Unbounded),
+            "c",
+        );
+
+        assert_eq!(
+            map.complement().iter().collect::<Vec<_>>(),
+            vec![
+                Span::from_range(..1),
+                Span::new(LeftBound::Excluded(8), RightBound::Included(10)),
+            ]
+        );
+
+        map.insert(.., "d");
+        assert_eq!(map.complement(), SpanSet::new());
+    }
+
+    #[test]
+    fn test_span_map_conversion() {
+        let mut map = SpanMap::<i32, ()>::new();
+        map.insert(1..5, ());
+
+        let set = SpanSet::from(map.clone());
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![Span::from_range(1..5)]);
+
+        let back: SpanMap<i32, ()> = set.into();
+        assert_eq!(back, map);
+    }
+
+    #[test]
+    fn test_spans_of() {
+        let mut map = SpanMap::<i32, &str>::new();
+        map.insert(1..5, "a");
+        map.insert(3..8, "b");
+        map.insert(8..10, "a");
+
+        assert_eq!(map.spans_of(&"a"), set_of(&[1..5, 8..10]));
+        assert_eq!(
+            map.spans_of(&"b").iter().collect::<Vec<_>>(),
+            vec![Span::from_range(3..8)]
+        );
+        assert_eq!(map.spans_of(&"c"), SpanSet::new());
+    }
+}
@@ -0,0 +1,364 @@
+use std::collections::BTreeMap;
+use std::ops::Add;
+use std::ops::Sub;
+
+use crate::bounds::LeftBound;
+use crate::SpanMap;
+use crate::Step;
+
+/// The side of an edit that a boundary lying at the edit point moves to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gravity {
+    /// The boundary stays before the inserted content.
+    Before,
+    /// The boundary moves past the inserted content.
+    After,
+}
+
+/// Specifies how the boundaries lying at the edit point of a [`SpanMap::splice_with`] move,
+/// depending on the kind of the bound.
+///
+/// The default moves every boundary past the inserted content, so that content inserted at the
+/// end of a span extends it, while content inserted at the start of a span does not.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stickiness {
+    /// How `LeftBound::Included` boundaries move.
+    pub included: Gravity,
+    /// How `LeftBound::Excluded` boundaries move.
+    pub excluded: Gravity,
+}
+
+impl Default for Stickiness {
+    fn default() -> Self {
+        Self {
+            included: Gravity::After,
+            excluded: Gravity::After,
+        }
+    }
+}
+
+impl<K, V> SpanMap<K, V>
+where
+    K: Clone + Ord,
+    V: Clone + Ord,
+{
+    /// Updates the spans for an edit that replaces `removed` keys at `at` with `inserted` keys,
+    /// the way an editor moves its decorations.
+    ///
+    /// Keys are offsets into a buffer, thus discrete, see [`Step`]. The content in
+    /// `at..at + removed` is replaced with `inserted` units of new content:
+    /// * Spans before the edit do not move.
+    /// * Spans after the edit move by `inserted - removed`.
+    /// * Spans containing the edit grow or shrink.
+    /// * Spans entirely inside the removed content disappear.
+    ///
+    /// Boundaries at the edit point move past the inserted content, see [`Stickiness`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use span_map::SpanMap;
+    ///
+    /// let mut map = SpanMap::<u32, &str>::new();
+    /// map.insert(10..20, "a");
+    ///
+    /// // Type 3 characters at offset 5
+    /// map.splice(5, 0, 3);
+    ///
+    /// let mut want = SpanMap::new();
+    /// want.insert(13..23, "a");
+    /// assert_eq!(map, want);
+    ///
+    /// // Delete 2 characters at offset 15
+    /// map.splice(15, 2, 0);
+    ///
+    /// let mut want = SpanMap::new();
+    /// want.insert(13..21, "a");
+    /// assert_eq!(map, want);
+    /// ```
+    pub fn splice(&mut self, at: K, removed: K, inserted: K)
+    where
+        K: Add<Output = K> + Sub<Output = K> + Step,
+    {
+        self.splice_with(at, removed, inserted, Stickiness::default());
+    }
+
+    /// Same as [`SpanMap::splice`], with configurable movement of the boundaries lying at the
+    /// edit point.
+    ///
+    /// A boundary lies at the edit point if its key is in `at..=at + removed`. Depending on the
+    /// [`Gravity`] configured for its kind of bound, it moves to `at` or to `at + inserted`.
+    /// Spans entirely inside the removed content disappear, whatever the kind of their bounds.
+    ///
+    /// # Examples
+    /// ```
+    /// # use span_map::SpanMap;
+    /// # use span_map::Gravity;
+    /// # use span_map::Stickiness;
+    ///
+    /// let mut map = SpanMap::<u32, &str>::new();
+    /// map.insert(10..20, "a");
+    ///
+    /// let stickiness = Stickiness {
+    ///     included: Gravity::Before,
+    ///     excluded: Gravity::Before,
+    /// };
+    ///
+    /// // Content typed at the start of the span belongs to the span
+    /// map.splice_with(10, 0, 3, stickiness);
+    ///
+    /// let mut want = SpanMap::new();
+    /// want.insert(10..23, "a");
+    /// assert_eq!(map, want);
+    /// ```
+    pub fn splice_with(&mut self, at: K, removed: K, inserted: K, stickiness: Stickiness)
+    where
+        K: Add<Output = K> + Sub<Output = K> + Step,
+    {
+        let removed_end = at.clone() + removed.clone();
+        let inserted_end = at.clone() + inserted.clone();
+
+        let move_key = |k: K, gravity: Gravity| -> K {
+            if k < at {
+                k
+            } else if k > removed_end {
+                k - removed.clone() + inserted.clone()
+            } else {
+                match gravity {
+                    Gravity::Before => at.clone(),
+                    Gravity::After => inserted_end.clone(),
+                }
+            }
+        };
+
+        // A segment starting inside the removed content keeps only its part at or after
+        // `removed_end`, or disappears if it ends before. Otherwise, an `Included(k)` and an
+        // `Excluded(k)` inside would both move to the edit point and leave a one-key segment.
+        // Bounds are compared in canonical form, so that `Excluded(at - 1)` is inside as well.
+        let start = LeftBound::Included(at.clone());
+        let end = LeftBound::Included(removed_end.clone());
+
+        let mut kept = Vec::with_capacity(self.m.len());
+        let mut it = std::mem::take(&mut self.m).into_iter().peekable();
+
+        while let Some((b, set)) = it.next() {
+            let canonical = b.clone().canonical();
+
+            if start <= canonical && canonical < end {
+                if it
+                    .peek()
+                    .is_some_and(|(next, _set)| next.clone().canonical() <= end)
+                {
+                    continue;
+                }
+                kept.push((end.clone(), set));
+            } else {
+                kept.push((b, set));
+            }
+        }
+
+        let mut m = BTreeMap::new();
+        let mut last = LeftBound::Unbounded;
+
+        for (b, set) in kept {
+            let b = match b {
+                LeftBound::Unbounded => LeftBound::Unbounded,
+                LeftBound::Included(k) => LeftBound::Included(move_key(k, stickiness.included)),
+                LeftBound::Excluded(k) => LeftBound::Excluded(move_key(k, stickiness.excluded)),
+            };
+
+            // A boundary moved to before its predecessor: the segments in between are gone.
+            let b = std::cmp::max(b, last);
+            last = b.clone();
+
+            m.insert(b, set);
+        }
+
+        self.m = m;
+        self.merge_all_adjacent();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounds::RightBound;
+    use crate::span::Span;
+
+    fn map_of(spans: &[(std::ops::Range<u32>, i32)]) -> SpanMap<u32, i32> {
+        let mut map = SpanMap::new();
+        for (range, value) in spans {
+            map.insert(range.clone(), *value);
+        }
+        map
+    }
+
+    #[test]
+    fn test_splice_insert() {
+        let mut map = map_of(&[(10..20, 1), (30..40, 2)]);
+
+        // Before all spans
+        map.splice(0, 0, 5);
+        assert_eq!(map, map_of(&[(15..25, 1), (35..45, 2)]));
+
+        // Inside a span
+        map.splice(20, 0, 5);
+        assert_eq!(map, map_of(&[(15..30, 1), (40..50, 2)]));
+
+        // After all spans
+        map.splice(100, 0, 5);
+        assert_eq!(map, map_of(&[(15..30, 1), (40..50, 2)]));
+    }
+
+    #[test]
+    fn test_splice_insert_at_boundary() {
+        // By default boundaries at the edit point move past the inserted content
+        let mut map = map_of(&[(10..20, 1), (20..30, 2)]);
+
+        map.splice(10, 0, 5);
+        assert_eq!(map, map_of(&[(15..25, 1), (25..35, 2)]));
+
+        map.splice(25, 0, 5);
+        assert_eq!(map, map_of(&[(15..30, 1), (30..40, 2)]));
+
+        // Boundaries stay before the inserted content
+        let before = Stickiness {
+            included: Gravity::Before,
+            excluded: Gravity::Before,
+        };
+
+        let mut map = map_of(&[(10..20, 1), (20..30, 2)]);
+
+        map.splice_with(10, 0, 5, before);
+        assert_eq!(map, map_of(&[(10..25, 1), (25..35, 2)]));
+
+        map.splice_with(25, 0, 5, before);
+        assert_eq!(map, map_of(&[(10..25, 1), (25..40, 2)]));
+    }
+
+    #[test]
+    fn test_splice_stickiness_by_bound_kind() {
+        let stickiness = Stickiness {
+            included: Gravity::Before,
+            excluded: Gravity::After,
+        };
+
+        // (10, 20] -> {1}
+        let mut map = SpanMap::<u32, i32>::new();
+        map.insert_span(
+            Span::new(LeftBound::Excluded(10), RightBound::Included(20)),
+            1,
+        );
+        // [30, 40) -> {2}
+        map.insert(30..40, 2);
+
+        map.splice_with(10, 0, 5, stickiness);
+        // Content inserted at the start of [35, 45) belongs to it
+        map.splice_with(35, 0, 5, stickiness);
+
+        let mut want = SpanMap::<u32, i32>::new();
+        want.insert_span(
+            Span::new(LeftBound::Excluded(15), RightBound::Included(25)),
+            1,
+        );
+        want.insert(35..50, 2);
+        assert_eq!(map, want);
+    }
+
+    #[test]
+    fn test_splice_remove() {
+        let mut map = map_of(&[(10..20, 1), (30..40, 2)]);
+
+        // Shrink a span and move the following one
+        map.splice(15, 3, 0);
+        assert_eq!(map, map_of(&[(10..17, 1), (27..37, 2)]));
+
+        // Remove across a span end
+        map.splice(15, 5, 0);
+        assert_eq!(map, map_of(&[(10..15, 1), (22..32, 2)]));
+
+        // Remove a whole span
+        map.splice(20, 15, 0);
+        assert_eq!(map, map_of(&[(10..15, 1)]));
+
+        // Remove everything
+        map.splice(0, 100, 0);
+        assert_eq!(map, SpanMap::new());
+
+        let before = Stickiness {
+            included: Gravity::Before,
+            excluded: Gravity::Before,
+        };
+
+        for stickiness in [Stickiness::default(), before] {
+            for inserted in [0, 3] {
+                // [12, 15] and (10, 19] lie inside the removed content 10..20
+                let mut map = SpanMap::<u32, i32>::new();
+                map.insert(12..=15, 1);
+                map.insert_span(
+                    Span::new(LeftBound::Excluded(10), RightBound::Included(19)),
+                    2,
+                );
+                map.splice_with(10, 10, inserted, stickiness);
+                assert_eq!(map, SpanMap::new());
+            }
+        }
+
+        // (9, 15] starts right before the removed content, with no key before it
+        let mut map = SpanMap::<u32, i32>::new();
+        map.insert_span(
+            Span::new(LeftBound::Excluded(9), RightBound::Included(15)),
+            1,
+        );
+        map.splice(10, 10, 0);
+        assert_eq!(map, SpanMap::new());
+
+        // The key at the end of the removed content survives
+        let mut map = SpanMap::<u32, i32>::new();
+        map.insert(15..=20, 1);
+        map.insert_span(
+            Span::new(LeftBound::Excluded(5), RightBound::Included(10)),
+            2,
+        );
+        map.splice(10, 10, 0);
+
+        let mut want = SpanMap::new();
+        want.insert(10..=10, 1);
+        want.insert_span(
+            Span::new(LeftBound::Excluded(5), RightBound::Excluded(10)),
+            2,
+        );
+        assert_eq!(map, want);
+    }
+
+    #[test]
+    fn test_splice_remove_merges_neighbours() {
+        // [10, 20) and [25, 30) become adjacent and merge
+        let mut map = map_of(&[(10..20, 1), (25..30, 1)]);
+
+        map.splice(20, 5, 0);
+        assert_eq!(map, map_of(&[(10..25, 1)]));
+    }
+
+    #[test]
+    fn test_splice_replace() {
+        let mut map = map_of(&[(10..20, 1), (15..30, 2)]);
+
+        // The removed region covers the boundaries at 15 and 20
+        map.splice(12, 10, 3);
+        assert_eq!(map, map_of(&[(10..15, 1), (15..23, 2)]));
+    }
+
+    #[test]
+    fn test_splice_unbounded() {
+        let mut map = SpanMap::<i64, i32>::new();
+        map.insert(..10, 1);
+        map.insert(20.., 2);
+
+        map.splice(0, 5, 1);
+
+        let mut want = SpanMap::new();
+        want.insert(..6, 1);
+        want.insert(16.., 2);
+        assert_eq!(map, want);
+    }
+}
@@ -0,0 +1,92 @@
+/// A key type with discrete values, where every value has a well-defined successor and
+/// predecessor.
+///
+/// For such keys, an excluded bound is equivalent to an included bound on the adjacent value,
+/// e.g., `(5, 9)` and `[6, 8]` contain the same integers. `Step` lets bounds be converted to
+/// this canonical, inclusive form, so that spans like `[1, 5]` and `[6, 9]` are recognized as
+/// adjacent.
+///
+/// # Examples
+/// ```
+/// # use span_map::Step;
+///
+/// assert_eq!(5u8.successor(), Some(6));
+/// assert_eq!(255u8.successor(), None);
+/// assert_eq!('\u{E000}'.predecessor(), Some('\u{D7FF}'));
+/// ```
+pub trait Step: Ord + Clone {
+    /// Returns the smallest value greater than `self`, or `None` if `self` is the maximum.
+    fn successor(&self) -> Option<Self>;
+
+    /// Returns the greatest value less than `self`, or `None` if `self` is the minimum.
+    fn predecessor(&self) -> Option<Self>;
+}
+
+macro_rules! impl_step_for_integers {
+    ($($t:ty),*) => {
+        $(
+            impl Step for $t {
+                fn successor(&self) -> Option<Self> {
+                    self.checked_add(1)
+                }
+
+                fn predecessor(&self) -> Option<Self> {
+                    self.checked_sub(1)
+                }
+            }
+        )*
+    };
+}
+
+impl_step_for_integers!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// `char` skips the surrogate range `U+D800..=U+DFFF`, which contains no valid `char`.
+impl Step for char {
+    fn successor(&self) -> Option<Self> {
+        match *self {
+            '\u{D7FF}' => Some('\u{E000}'),
+            c => char::from_u32(c as u32 + 1),
+        }
+    }
+
+    fn predecessor(&self) -> Option<Self> {
+        match *self {
+            '\u{E000}' => Some('\u{D7FF}'),
+            c => (c as u32).checked_sub(1).and_then(char::from_u32),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_integers() {
+        assert_eq!(0u8.successor(), Some(1));
+        assert_eq!(u8::MAX.successor(), None);
+        assert_eq!(0u8.predecessor(), None);
+        assert_eq!(u8::MAX.predecessor(), Some(254));
+
+        assert_eq!((-1i32).successor(), Some(0));
+        assert_eq!(i32::MAX.successor(), None);
+        assert_eq!(i32::MIN.predecessor(), None);
+        assert_eq!(0i32.predecessor(), Some(-1));
+
+        assert_eq!(u128::MAX.successor(), None);
+        assert_eq!(isize::MIN.successor(), Some(isize::MIN + 1));
+    }
+
+    #[test]
+    fn test_step_char() {
+        assert_eq!('a'.successor(), Some('b'));
+        assert_eq!('b'.predecessor(), Some('a'));
+
+        assert_eq!('\0'.predecessor(), None);
+        assert_eq!(char::MAX.successor(), None);
+
+        // Surrogates are skipped
+        assert_eq!('\u{D7FF}'.successor(), Some('\u{E000}'));
+        assert_eq!('\u{E000}'.predecessor(), Some('\u{D7FF}'));
+    }
+}
@@ -0,0 +1,169 @@
+//! Bulk construction of a [`SpanMap`] with a sweep line over all span endpoints.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+use crate::bounds::LeftBound;
+use crate::Span;
+use crate::SpanMap;
+
+impl<K, V> SpanMap<K, V>
+where
+    K: Clone + Ord,
+    V: Clone + Ord,
+{
+    /// Builds a map from `(span, value)` pairs, with the same result as inserting them one by
+    /// one with [`SpanMap::insert_span`].
+    ///
+    /// Instead of splitting and merging segments for every insertion, all span endpoints are
+    /// sorted and the segments are built in a single sweep, counting the spans of every value
+    /// that cover the sweep position. It takes `O(n log n)` time for `n` spans, plus the cost
+    /// of cloning the value set of every segment produced.
+    ///
+    /// Empty spans are ignored.
+    ///
+    /// # Examples
+    /// ```
+    /// # use span_map::Span;
+    /// # use span_map::SpanMap;
+    ///
+    /// let map = SpanMap::from_spans([
+    ///     (Span::from_range(1..5), "a"),
+    ///     (Span::from_range(3..8), "b"),
+    ///     (Span::from_range(4..6), "a"),
+    /// ]);
+    ///
+    /// let mut want = SpanMap::new();
+    /// want.insert(1..5, "a");
+    /// want.insert(3..8, "b");
+    /// want.insert(4..6, "a");
+    /// assert_eq!(map, want);
+    /// ```
+    pub fn from_spans<I>(spans: I) -> Self
+    where
+        I: IntoIterator<Item = (Span<K>, V)>,
+    {
+        // Every span adds its value at its start, and removes it where the span ends.
+        let mut events: Vec<(LeftBound<K>, bool, V)> = Vec::new();
+        for (span, value) in spans {
+            if span.is_empty() {
+                continue;
+            }
+
+            let (left, right) = span.into_bounds();
+            if let Some(end) = right.adjacent_left() {
+                events.push((end, false, value.clone()));
+            }
+            events.push((left, true, value));
+        }
+
+        events.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+
+        let mut m = BTreeMap::new();
+        m.insert(LeftBound::Unbounded, BTreeSet::new());
+
+        let mut counts: BTreeMap<V, usize> = BTreeMap::new();
+        let mut last_set: BTreeSet<V> = BTreeSet::new();
+        let mut events = events.into_iter().peekable();
+
+        while let Some((bound, _, _)) = events.peek() {
+            let bound = bound.clone();
+
+            while let Some((_, added, value)) = events.next_if(|(b, _, _)| b == &bound) {
+                if added {
+                    *counts.entry(value).or_default() += 1;
+                } else if let Some(n) = counts.get_mut(&value) {
+                    *n -= 1;
+                    if *n == 0 {
+                        counts.remove(&value);
+                    }
+                }
+            }
+
+            if counts.len() != last_set.len() || !counts.keys().eq(last_set.iter()) {
+                last_set = counts.keys().cloned().collect();
+                m.insert(bound, last_set.clone());
+            }
+        }
+
+        SpanMap { m }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounds::RightBound;
+    use crate::test_rng::TestRng;
+
+    fn random_span(rng: &mut TestRng) -> Span<i32> {
+        let left = match rng.below(4) {
+            0 => LeftBound::Unbounded,
+            1 => LeftBound::Excluded(rng.below(20) as i32),
+            _ => LeftBound::Included(rng.below(20) as i32),
+        };
+        let right = match rng.below(4) {
+            0 => RightBound::Unbounded,
+            1 => RightBound::Included(rng.below(20) as i32),
+            _ => RightBound::Excluded(rng.below(20) as i32),
+        };
+        Span::new(left, right)
+    }
+
+    #[test]
+    fn test_from_spans_empty() {
+        let map = SpanMap::<i32, i32>::from_spans([]);
+        assert_eq!(map, SpanMap::new());
+
+        #[allow(clippy::reversed_empty_ranges)]
+        let map = SpanMap::from_spans([(Span::from_range(5..1), 1), (Span::from_range(3..3), 2)]);
+        assert_eq!(map, SpanMap::new());
+    }
+
+    #[test]
+    fn test_from_spans_duplicates_and_adjacent() {
+        let map = SpanMap::from_spans([
+            (Span::from_range(1..5), "a"),
+            (Span::from_range(1..5), "a"),
+            (Span::from_range(5..9), "a"),
+            (Span::from_range(2..3), "a"),
+            (
+                Span::new(LeftBound::Excluded(9), RightBound::Unbounded),
+                "b",
+            ),
+        ]);
+
+        let mut want = SpanMap::new();
+        want.insert(1..9, "a");
+        want.insert_span(
+            Span::new(LeftBound::Excluded(9), RightBound::Unbounded),
+            "b",
+        );
+        assert_eq!(map, want);
+    }
+
+    #[test]
+    fn test_from_spans_same_as_inserts() {
+        let mut rng = TestRng::new(7);
+
+        for round in 0..200 {
+            let n = rng.below(12) as usize;
+            let spans: Vec<_> = (0..n)
+                .map(|_| (random_span(&mut rng), rng.below(4) as i32))
+                .collect();
+
+            let mut want = SpanMap::new();
+            for (span, v) in spans.clone() {
+                want.insert_span(span, v);
+            }
+
+            assert_eq!(
+                SpanMap::from_spans(spans.clone()),
+                want,
+                "round {}: {:?}",
+                round,
+                spans
+            );
+        }
+    }
+}
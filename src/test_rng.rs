@@ -0,0 +1,21 @@
+//! A small deterministic pseudo random generator, for randomized tests.
+
+/// A linear congruential generator: the same seed always gives the same sequence.
+pub(crate) struct TestRng {
+    state: u64,
+}
+
+impl TestRng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Returns a number in `0..n`.
+    pub(crate) fn below(&mut self, n: u64) -> u64 {
+        self.state = self
+            .state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.state >> 33) % n
+    }
+}
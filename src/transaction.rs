@@ -0,0 +1,225 @@
+//! All-or-nothing mutation of a [`SpanMap`], with an undo log.
+
+use std::ops::RangeBounds;
+
+use crate::Change;
+use crate::ChangeKind;
+use crate::PatchOp;
+use crate::Span;
+use crate::SpanMap;
+use crate::SpanPatch;
+
+/// A guard that records the changes made to a [`SpanMap`], so that they can be undone, created
+/// by [`SpanMap::transaction`].
+///
+/// The changes are applied to the map right away. Unless [`Transaction::commit`] is called,
+/// they are rolled back when the transaction is dropped, e.g., when returning early on an
+/// error.
+#[derive(Debug)]
+pub struct Transaction<'a, K, V>
+where
+    K: Clone + Ord,
+    V: Clone + Ord,
+{
+    map: &'a mut SpanMap<K, V>,
+
+    /// The effective changes made so far, in order.
+    log: Vec<Change<K, V>>,
+}
+
+impl<K, V> SpanMap<K, V>
+where
+    K: Clone + Ord,
+    V: Clone + Ord,
+{
+    /// Starts a transaction on the map.
+    ///
+    /// # Examples
+    /// ```
+    /// # use span_map::SpanMap;
+    ///
+    /// let mut map = SpanMap::new();
+    /// map.insert(0..10, "old");
+    ///
+    /// fn reconfigure(map: &mut SpanMap<i32, &'static str>, fail: bool) -> Result<(), ()> {
+    ///     let mut tx = map.transaction();
+    ///     tx.remove(0..10, "old");
+    ///     if fail {
+    ///         return Err(()); // `tx` is dropped and rolled back
+    ///     }
+    ///     tx.insert(5..20, "new");
+    ///     tx.commit();
+    ///     Ok(())
+    /// }
+    ///
+    /// let before = map.clone();
+    /// assert!(reconfigure(&mut map, true).is_err());
+    /// assert_eq!(map, before);
+    ///
+    /// assert!(reconfigure(&mut map, false).is_ok());
+    /// assert_eq!(map.get(&3).count(), 0);
+    /// assert_eq!(map.get(&15).copied().collect::<Vec<_>>(), vec!["new"]);
+    /// ```
+    pub fn transaction(&mut self) -> Transaction<'_, K, V> {
+        Transaction {
+            map: self,
+            log: Vec::new(),
+        }
+    }
+}
+
+impl<K, V> Transaction<'_, K, V>
+where
+    K: Clone + Ord,
+    V: Clone + Ord,
+{
+    /// Returns the map, with the changes made so far.
+    pub fn map(&self) -> &SpanMap<K, V> {
+        self.map
+    }
+
+    /// Same as [`SpanMap::get`].
+    pub fn get(&self, key: &K) -> impl Iterator<Item = &V> {
+        self.map.get(key)
+    }
+
+    /// Same as [`SpanMap::insert`].
+    pub fn insert<R>(&mut self, range: R, value: V)
+    where
+        R: RangeBounds<K>,
+    {
+        let changes = self.map.insert_tracked(range, value);
+        self.log.extend(changes);
+    }
+
+    /// Same as [`SpanMap::remove`].
+    pub fn remove<R>(&mut self, range: R, value: V)
+    where
+        R: RangeBounds<K>,
+    {
+        let changes = self.map.remove_tracked(range, value);
+        self.log.extend(changes);
+    }
+
+    /// Same as [`SpanMap::insert_span`].
+    pub fn insert_span(&mut self, span: Span<K>, value: V) {
+        self.insert(span, value);
+    }
+
+    /// Same as [`SpanMap::remove_span`].
+    pub fn remove_span(&mut self, span: Span<K>, value: V) {
+        self.remove(span, value);
+    }
+
+    /// Keeps the changes, and returns them as a patch, e.g., to send them to replicas.
+    pub fn commit(mut self) -> SpanPatch<K, V> {
+        self.take_patch()
+    }
+
+    /// Undoes the changes made in the transaction.
+    ///
+    /// This is the same as dropping the transaction.
+    pub fn rollback(self) {}
+
+    /// Empties the log, and returns the changes as a patch.
+    fn take_patch(&mut self) -> SpanPatch<K, V> {
+        std::mem::take(&mut self.log)
+            .into_iter()
+            .map(PatchOp::from)
+            .collect()
+    }
+}
+
+impl<K, V> Drop for Transaction<'_, K, V>
+where
+    K: Clone + Ord,
+    V: Clone + Ord,
+{
+    fn drop(&mut self) {
+        if self.log.is_empty() {
+            return;
+        }
+
+        let log = std::mem::take(&mut self.log);
+
+        // Every change was made over its whole span: undoing them in reverse order restores
+        // the map.
+        self.map.batch(|b| {
+            for change in log.into_iter().rev() {
+                match change.kind {
+                    ChangeKind::Added => b.remove_span(change.span, change.value),
+                    ChangeKind::Removed => b.insert_span(change.span, change.value),
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> SpanMap<i32, u8> {
+        let mut map = SpanMap::new();
+        map.insert(0..10, 1);
+        map.insert(5..15, 2);
+        map.insert(20.., 3);
+        map
+    }
+
+    #[test]
+    fn test_rollback() {
+        let mut map = sample();
+
+        let mut tx = map.transaction();
+        tx.remove(3..7, 1);
+        tx.insert(..30, 2);
+        tx.insert(8..9, 1); // already present, nothing to undo
+        tx.remove(.., 3);
+        assert_eq!(tx.get(&25).copied().collect::<Vec<_>>(), vec![2]);
+        tx.rollback();
+        assert_eq!(map, sample());
+
+        // Changes that undo earlier ones are rolled back in reverse order
+        let mut tx = map.transaction();
+        tx.insert(30..40, 4);
+        tx.remove(32..35, 4);
+        tx.insert(33..38, 4);
+        tx.remove(.., 1);
+        tx.insert(2..4, 1);
+        tx.rollback();
+        assert_eq!(map, sample());
+
+        // Dropping without commit rolls back as well
+        {
+            let mut tx = map.transaction();
+            tx.insert(100..200, 4);
+            tx.remove(0..5, 1);
+        }
+        assert_eq!(map, sample());
+    }
+
+    #[test]
+    fn test_commit() {
+        let mut map = sample();
+
+        let mut tx = map.transaction();
+        tx.remove(0..10, 1);
+        tx.insert(0..3, 4);
+        tx.insert(1..2, 4);
+        let patch = tx.commit();
+
+        let mut want = sample();
+        want.remove(0..10, 1);
+        want.insert(0..3, 4);
+        assert_eq!(map, want);
+
+        // The returned patch replays the transaction elsewhere, and undoes it here
+        let mut replica = sample();
+        replica.apply(&patch).unwrap();
+        assert_eq!(replica, want);
+
+        map.apply(&patch.invert()).unwrap();
+        assert_eq!(map, sample());
+    }
+}